@@ -0,0 +1,84 @@
+//! Compares the binary encoding of claim lists with their comma-joined text,
+//! in size and in encode/decode time. Run with `cargo bench --bench encoding`.
+
+use claims::claim::{claims_from_strs, Claim};
+use claims::encoding::{decode_claims, encode_claims};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 2_000;
+
+fn workloads() -> Vec<(&'static str, Vec<String>)> {
+    vec![
+        ("single global", vec![String::from("admin:*")]),
+        (
+            "cookie sized",
+            vec![
+                "read:projects.1",
+                "read:projects.2.settings",
+                "write:projects.1",
+                "read:users.me",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        ),
+        (
+            "deep shared prefixes",
+            (0..200)
+                .map(|i| format!("read:organizations.acme.projects.{}.settings", i))
+                .chain((0..50).map(|i| format!("write:organizations.acme.projects.{}", i)))
+                .collect(),
+        ),
+        (
+            "many verbs",
+            (0..500)
+                .map(|i| format!("verb{}:resources.{}.{}", i % 25, i / 25, i))
+                .collect(),
+        ),
+    ]
+}
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    println!(
+        "{:<22} {:>7} {:>10} {:>10} {:>7} {:>12} {:>12} {:>12}",
+        "workload", "claims", "text B", "binary B", "ratio", "encode", "decode", "parse text"
+    );
+
+    for (name, strings) in workloads() {
+        let claims: Vec<Claim> = claims_from_strs(strings.iter()).unwrap();
+        let text = strings.join(",");
+        let encoded = encode_claims(claims.iter());
+        assert_eq!(decode_claims(&encoded).unwrap(), claims);
+
+        let encode = time(|| {
+            black_box(encode_claims(black_box(&claims).iter()));
+        });
+        let decode = time(|| {
+            black_box(decode_claims(black_box(&encoded)).unwrap());
+        });
+        let parse = time(|| {
+            black_box(claims_from_strs(black_box(&text).split(',')).unwrap());
+        });
+
+        println!(
+            "{:<22} {:>7} {:>10} {:>10} {:>6.2}x {:>12?} {:>12?} {:>12?}",
+            name,
+            claims.len(),
+            text.len(),
+            encoded.len(),
+            text.len() as f64 / encoded.len() as f64,
+            encode,
+            decode,
+            parse
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use crate::claim::{claim_check, parse_query, Claim};
+use crate::error::Error;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Granted,
+    Denied,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Decision::Granted => write!(f, "granted"),
+            Decision::Denied => write!(f, "denied"),
+        }
+    }
+}
+
+impl FromStr for Decision {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "granted" => Ok(Decision::Granted),
+            "denied" => Ok(Decision::Denied),
+            _ => Err(Error::Syntax(format!(
+                "the given decision {} is not valid",
+                s
+            ))),
+        }
+    }
+}
+
+/// One authorization decision, as given to an `AuditSink`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    /// Unix timestamp, in seconds.
+    pub timestamp: u64,
+    pub principal: String,
+    pub context: Option<String>,
+    /// The query as given, so malformed queries are recorded verbatim.
+    pub query: String,
+    /// The first claim that allowed the query, if any.
+    pub matched: Option<String>,
+    pub decision: Decision,
+}
+
+pub trait AuditSink {
+    fn record(&self, record: &AuditRecord) -> io::Result<()>;
+}
+
+/// Checks queries against a list of claims and records every decision in
+/// its sink.
+#[derive(Debug)]
+pub struct Auditor<S: AuditSink> {
+    sink: S,
+}
+
+impl<S: AuditSink> Auditor<S> {
+    pub fn new(sink: S) -> Self {
+        Self { sink }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    /// Like `claim_check` over the list of claims. Fails only if the sink
+    /// could not record the decision.
+    pub fn check<'a, I>(
+        &self,
+        mut claims: I,
+        query: &Claim,
+        principal: &str,
+        context: Option<&str>,
+    ) -> io::Result<bool>
+    where
+        I: Iterator<Item = &'a Claim>,
+    {
+        let matched = claims.find(|c| claim_check(c, query));
+        self.record(
+            query.to_string(),
+            matched.map(|c| c.to_string()),
+            principal,
+            context,
+        )
+    }
+
+    /// Like `check`, but malformed queries are recorded and denied.
+    pub fn check_str<'a, I>(
+        &self,
+        mut claims: I,
+        query: &str,
+        principal: &str,
+        context: Option<&str>,
+    ) -> io::Result<bool>
+    where
+        I: Iterator<Item = &'a Claim>,
+    {
+        let matched = match parse_query(query) {
+            Ok(parsed) => claims.find(|c| claim_check(c, &parsed)),
+            Err(_) => None,
+        };
+        self.record(
+            String::from(query),
+            matched.map(|c| c.to_string()),
+            principal,
+            context,
+        )
+    }
+
+    fn record(
+        &self,
+        query: String,
+        matched: Option<String>,
+        principal: &str,
+        context: Option<&str>,
+    ) -> io::Result<bool> {
+        let decision = if matched.is_some() {
+            Decision::Granted
+        } else {
+            Decision::Denied
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        self.sink.record(&AuditRecord {
+            timestamp,
+            principal: String::from(principal),
+            context: context.map(String::from),
+            query,
+            matched,
+            decision,
+        })?;
+        Ok(decision == Decision::Granted)
+    }
+}
+
+/// Keeps every record in memory.
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    records: Mutex<Vec<AuditRecord>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn records(&self) -> Vec<AuditRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+impl AuditSink for MemorySink {
+    fn record(&self, record: &AuditRecord) -> io::Result<()> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+/// Writes every record as a JSON object on its own line, e.g. to a file
+/// opened in append mode.
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub struct JsonLinesSink<W: io::Write> {
+    writer: Mutex<W>,
+}
+
+#[cfg(feature = "json")]
+impl JsonLinesSink<std::fs::File> {
+    /// Opens the file in append mode, creating it if needed.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+#[cfg(feature = "json")]
+impl<W: io::Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<W: io::Write> AuditSink for JsonLinesSink<W> {
+    fn record(&self, record: &AuditRecord) -> io::Result<()> {
+        let line = serde_json::json!({
+            "timestamp": record.timestamp,
+            "principal": record.principal,
+            "context": record.context,
+            "query": record.query,
+            "matched": record.matched,
+            "decision": record.decision.to_string(),
+        });
+
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "{}", line)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims() -> Vec<Claim> {
+        crate::test_util::claims(&["read:projects", "admin:users.1"])
+    }
+
+    #[test]
+    fn test_decision_from_str() {
+        assert_eq!("granted".parse(), Ok(Decision::Granted));
+        assert_eq!("denied".parse(), Ok(Decision::Denied));
+        assert_eq!(
+            "maybe".parse::<Decision>(),
+            Err(Error::Syntax(
+                "the given decision maybe is not valid".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_memory_sink() {
+        let auditor = Auditor::new(MemorySink::new());
+        let list = claims();
+
+        let granted = auditor.check(
+            list.iter(),
+            &Claim::new("read", "projects.42"),
+            "alice",
+            Some("req-1"),
+        );
+        let denied = auditor.check_str(list.iter(), "admin:users.2", "bob", None);
+        let malformed = auditor.check_str(list.iter(), "garbage", "bob", None);
+
+        assert!(granted.unwrap());
+        assert!(!denied.unwrap());
+        assert!(!malformed.unwrap());
+
+        let records = auditor.sink().records();
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[0].principal, "alice");
+        assert_eq!(records[0].context, Some("req-1".to_string()));
+        assert_eq!(records[0].query, "read:projects.42");
+        assert_eq!(records[0].matched, Some("read:projects".to_string()));
+        assert_eq!(records[0].decision, Decision::Granted);
+
+        assert_eq!(records[1].principal, "bob");
+        assert_eq!(records[1].context, None);
+        assert_eq!(records[1].query, "admin:users.2");
+        assert_eq!(records[1].matched, None);
+        assert_eq!(records[1].decision, Decision::Denied);
+
+        assert_eq!(records[2].query, "garbage");
+        assert_eq!(records[2].decision, Decision::Denied);
+    }
+
+    struct FailingSink;
+
+    impl AuditSink for FailingSink {
+        fn record(&self, _record: &AuditRecord) -> io::Result<()> {
+            Err(io::Error::other("disk full"))
+        }
+    }
+
+    #[test]
+    fn test_failing_sink() {
+        let auditor = Auditor::new(FailingSink);
+        let list = claims();
+        assert!(auditor
+            .check_str(list.iter(), "read:projects", "alice", None)
+            .is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_lines_sink() {
+        let auditor = Auditor::new(JsonLinesSink::new(Vec::new()));
+        let list = claims();
+        auditor
+            .check_str(list.iter(), "read:projects.1", "alice", Some("req-1"))
+            .unwrap();
+        auditor
+            .check_str(list.iter(), "read:users", "bob", None)
+            .unwrap();
+
+        let output = String::from_utf8(auditor.into_sink().into_inner()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["principal"], "alice");
+        assert_eq!(lines[0]["context"], "req-1");
+        assert_eq!(lines[0]["query"], "read:projects.1");
+        assert_eq!(lines[0]["matched"], "read:projects");
+        assert_eq!(lines[0]["decision"], "granted");
+        assert_eq!(lines[1]["principal"], "bob");
+        assert_eq!(lines[1]["context"], serde_json::Value::Null);
+        assert_eq!(lines[1]["matched"], serde_json::Value::Null);
+        assert_eq!(lines[1]["decision"], "denied");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_lines_file() {
+        let path = std::env::temp_dir().join(format!("claims-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let list = claims();
+        for _ in 0..2 {
+            let auditor = Auditor::new(JsonLinesSink::open(&path).unwrap());
+            auditor
+                .check_str(list.iter(), "read:projects", "alice", None)
+                .unwrap();
+        }
+
+        let output = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(output.lines().count(), 2);
+    }
+}
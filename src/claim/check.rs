@@ -1,8 +1,12 @@
 use crate::claim::claim_from_str::claim_from_str;
+use crate::claim::invalid_query::parse_query;
 use crate::claim::Claim;
+use crate::error::Error;
 
+/// A malformed query never matches, like in the other `_str` helpers; see
+/// `try_claim_check_str` and `set_invalid_query_hook`.
 pub fn claim_check_str(claim: &Claim, query: &str) -> bool {
-    let parse_result = claim_from_str(query);
+    let parse_result = parse_query(query);
     if let Ok(parsed) = parse_result {
         claim_check(claim, &parsed)
     } else {
@@ -11,7 +15,7 @@ pub fn claim_check_str(claim: &Claim, query: &str) -> bool {
 }
 
 pub fn claim_exact_str(claim: &Claim, query: &str) -> bool {
-    let parse_result = claim_from_str(query);
+    let parse_result = parse_query(query);
     if let Ok(parsed) = parse_result {
         claim_exact(claim, &parsed)
     } else {
@@ -19,12 +23,28 @@ pub fn claim_exact_str(claim: &Claim, query: &str) -> bool {
     }
 }
 
+pub fn try_claim_check_str(claim: &Claim, query: &str) -> Result<bool, Error> {
+    let parsed = claim_from_str(query)?;
+    Ok(claim_check(claim, &parsed))
+}
+
+pub fn try_claim_exact_str(claim: &Claim, query: &str) -> Result<bool, Error> {
+    let parsed = claim_from_str(query)?;
+    Ok(claim_exact(claim, &parsed))
+}
+
 pub fn claim_exact(claim: &Claim, query: &Claim) -> bool {
     claim.verb == query.verb && claim.subject == query.subject
 }
 
 pub fn claim_check(claim: &Claim, query: &Claim) -> bool {
-    if claim.verb != query.verb {
+    claim_check_parts(claim, &query.verb, &query.subject)
+}
+
+/// Like `claim_check` with the query given as its verb and subject (empty
+/// for global), without building a `Claim`. The subject is not validated.
+pub fn claim_check_parts(claim: &Claim, verb: &str, subject: &str) -> bool {
+    if claim.verb != verb {
         return false;
     }
 
@@ -32,17 +52,10 @@ pub fn claim_check(claim: &Claim, query: &Claim) -> bool {
         return true;
     }
 
-    if query.is_global() {
-        return false;
-    }
-
-    if claim.subject == query.subject {
-        return true;
+    match subject.strip_prefix(claim.subject.as_str()) {
+        Some(rest) => rest.is_empty() || rest.starts_with('.'),
+        None => false,
     }
-
-    query
-        .subject
-        .starts_with(format!("{}.", claim.subject).as_str())
 }
 
 #[cfg(test)]
@@ -51,12 +64,12 @@ mod tests {
 
     fn check_valid(claim: &Claim, query: &str) {
         let msg = format!("claim: '{:?}' with query '{}' should work", claim, query);
-        assert!(claim_check_str(claim, query), msg)
+        assert!(claim_check_str(claim, query), "{}", msg)
     }
 
     fn check_invalid(claim: &Claim, query: &str) {
         let msg = format!("claim: '{:?}' with query '{}' should fail", claim, query);
-        assert!(!claim_check_str(claim, query), msg)
+        assert!(!claim_check_str(claim, query), "{}", msg)
     }
 
     #[test]
@@ -75,6 +88,40 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn check_parts() {
+        let claim = Claim::new("read", "a.b");
+        assert!(claim_check_parts(&claim, "read", "a.b"));
+        assert!(claim_check_parts(&claim, "read", "a.b.c"));
+        assert!(!claim_check_parts(&claim, "read", "a.bc"));
+        assert!(!claim_check_parts(&claim, "read", "a"));
+        assert!(!claim_check_parts(&claim, "read", ""));
+        assert!(!claim_check_parts(&claim, "write", "a.b"));
+
+        let global = Claim::new("read", "");
+        assert!(claim_check_parts(&global, "read", ""));
+        assert!(claim_check_parts(&global, "read", "anything.at.all"));
+    }
+
+    #[test]
+    fn try_with_invalid_query() {
+        let claim = Claim::new("read", "");
+        let err = Err(Error::Syntax(
+            "the given claim whatever-this-is is not valid".to_string(),
+        ));
+        assert_eq!(try_claim_check_str(&claim, "whatever-this-is"), err);
+        assert_eq!(try_claim_exact_str(&claim, "whatever-this-is"), err);
+    }
+
+    #[test]
+    fn try_with_valid_query() {
+        let claim = Claim::new("read", "a");
+        assert_eq!(try_claim_check_str(&claim, "read:a.b"), Ok(true));
+        assert_eq!(try_claim_check_str(&claim, "read:b"), Ok(false));
+        assert_eq!(try_claim_exact_str(&claim, "read:a.*"), Ok(true));
+        assert_eq!(try_claim_exact_str(&claim, "read:a.b"), Ok(false));
+    }
+
     #[test]
     fn test_exact_with_same() {
         assert!(claim_check_str(&Claim::new("read", ""), "read:*"));
@@ -1,5 +1,6 @@
 use crate::claim::{Claim, CLAIM_REGEX};
 use crate::error::Error;
+use std::fmt;
 
 pub fn claim_from_str(claim_str: &str) -> Result<Claim, Error> {
     let c = CLAIM_REGEX.captures(claim_str);
@@ -14,14 +15,19 @@ pub fn claim_from_str(claim_str: &str) -> Result<Claim, Error> {
     let subject_match = caps.get(2).unwrap().as_str();
 
     let subject = parse_subject(subject_match);
+    // the grammar allows repeated dots, but no segment can be empty
+    if !subject.is_empty() && subject.split('.').any(|s| s.is_empty()) {
+        return Err(err_not_parsed(claim_str));
+    }
     Ok(Claim::new(&verb, &subject))
 }
 
-pub fn claims_from_strs<'a, I>(claim_strs: I) -> Result<Vec<Claim>, Error>
+pub fn claims_from_strs<I, S>(claim_strs: I) -> Result<Vec<Claim>, Error>
 where
-    I: Iterator<Item = &'a &'a str>,
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
 {
-    let parsed = claim_strs.map(|c| claim_from_str(c));
+    let parsed = claim_strs.map(|c| claim_from_str(c.as_ref()));
     let list: Result<Vec<Claim>, Error> = parsed.collect();
     match list {
         Ok(_) => {
@@ -34,6 +40,74 @@ where
     }
 }
 
+/// An entry of a claim list that could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimParseError {
+    /// 0-based position of the entry in the list.
+    pub index: usize,
+    pub input: String,
+    pub error: Error,
+}
+
+impl fmt::Display for ClaimParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "entry {}: {}", self.index, self.error)
+    }
+}
+
+/// Like `claims_from_strs`, but validates every entry and returns all the
+/// errors, in the order of the list, instead of stopping at the first one.
+pub fn claims_from_strs_all_errors<I, S>(claim_strs: I) -> Result<Vec<Claim>, Vec<ClaimParseError>>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut claims = Vec::new();
+    let mut errors = Vec::new();
+    for (index, claim_str) in claim_strs.enumerate() {
+        let claim_str = claim_str.as_ref();
+        match claim_from_str(claim_str) {
+            Ok(claim) => claims.push(claim),
+            Err(error) => errors.push(ClaimParseError {
+                index,
+                input: String::from(claim_str),
+                error,
+            }),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    claims.sort();
+    claims.dedup();
+    Ok(claims)
+}
+
+/// Builds a claim from its verb and subject with the same grammar and
+/// normalization as `claim_from_str`. An empty subject or `*` is global.
+pub fn claim_from_parts(verb: &str, subject: &str) -> Result<Claim, Error> {
+    let subject = if subject.is_empty() { "*" } else { subject };
+    claim_from_str(&format!("{}:{}", verb, subject))
+}
+
+/// Whether the claim is exactly what `claim_from_str` would give for its
+/// string form, i.e. it follows the grammar and is normalized.
+pub fn claim_is_valid(claim: &Claim) -> bool {
+    match claim_from_str(&claim.to_string()) {
+        Ok(parsed) => parsed == *claim,
+        Err(_) => false,
+    }
+}
+
+/// Returns the claims of the list that are not valid, see `claim_is_valid`.
+pub fn claims_invalid<'a, I>(claims: I) -> Vec<&'a Claim>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    claims.filter(|c| !claim_is_valid(c)).collect()
+}
+
 fn parse_subject(s: &str) -> String {
     match s {
         "*" | "" => String::from(""),
@@ -49,7 +123,7 @@ fn parse_subject(s: &str) -> String {
     }
 }
 
-fn err_not_parsed(claim_str: &str) -> Error {
+pub(crate) fn err_not_parsed(claim_str: &str) -> Error {
     Error::Syntax(format!("the given claim {} is not valid", claim_str))
 }
 
@@ -57,16 +131,47 @@ fn err_not_parsed(claim_str: &str) -> Error {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_all_errors_when_valid() {
+        let list = ["read:b", "read:a.*", "read:a", "admin:*"];
+        assert_eq!(
+            claims_from_strs_all_errors(list.iter()),
+            claims_from_strs(list.iter()).map_err(|_| Vec::new())
+        );
+        assert_eq!(
+            claims_from_strs_all_errors(Vec::<String>::new().into_iter()),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_all_errors_when_invalid() {
+        let list = ["read:a", " read:b", "read:c", "bad-stuff.*", "read:*", ""];
+        let errors = claims_from_strs_all_errors(list.iter()).unwrap_err();
+
+        let found: Vec<(usize, &str)> =
+            errors.iter().map(|e| (e.index, e.input.as_str())).collect();
+        assert_eq!(found, vec![(1, " read:b"), (3, "bad-stuff.*"), (5, "")]);
+        assert_eq!(
+            errors[1].error,
+            Error::Syntax("the given claim bad-stuff.* is not valid".to_string())
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "entry 3: the given claim bad-stuff.* is not valid"
+        );
+    }
+
     fn check_valid(claim: &str) {
         let res = claim_from_str(claim);
         let msg = format!("claim: '{}' should work but failed with {:?}", claim, res);
-        assert!(res.is_ok(), msg)
+        assert!(res.is_ok(), "{}", msg)
     }
 
     fn check_invalid(claim: &str) {
         let res = claim_from_str(claim);
         let msg = format!("claim: '{}' should fail", claim);
-        assert!(res.is_err(), msg)
+        assert!(res.is_err(), "{}", msg)
     }
 
     #[test]
@@ -99,6 +204,9 @@ mod tests {
             "read:.paco",
             "read:*.*",
             "read:*.some.stuff",
+            "read:a..",
+            "read:a...*",
+            "read:a..b",
         ];
         for x in list.iter() {
             check_invalid(x);
@@ -137,6 +245,73 @@ mod tests {
         assert_eq!(claims_from_strs(strings.iter()), Err(err_not_parsed("bad")));
     }
 
+    #[test]
+    fn from_parts_valid() {
+        assert_eq!(claim_from_parts("read", ""), Ok(Claim::new("read", "")));
+        assert_eq!(claim_from_parts("read", "*"), Ok(Claim::new("read", "")));
+        assert_eq!(claim_from_parts("read", "a."), Ok(Claim::new("read", "a")));
+        assert_eq!(
+            claim_from_parts("read", "a.b.*"),
+            Ok(Claim::new("read", "a.b"))
+        );
+    }
+
+    #[test]
+    fn from_parts_invalid() {
+        let list = [
+            ("bad verb", "x"),
+            ("", "x"),
+            ("read", ".a"),
+            ("read", "a b"),
+            ("read", "a:b"),
+            ("read", "*.a"),
+            ("read", "a.."),
+            ("read", "a...*"),
+            ("read", "a..b"),
+        ];
+        for (verb, subject) in list.iter() {
+            assert_eq!(
+                claim_from_parts(verb, subject),
+                Err(err_not_parsed(&format!("{}:{}", verb, subject)))
+            );
+        }
+    }
+
+    #[test]
+    fn is_valid() {
+        assert!(claim_is_valid(&Claim::new("read", "")));
+        assert!(claim_is_valid(&Claim::new("read", "a.b")));
+        assert!(!claim_is_valid(&Claim::new("read", "a.")));
+        assert!(!claim_is_valid(&Claim::new("read", "a.*")));
+        assert!(!claim_is_valid(&Claim::new("read", "*")));
+        assert!(!claim_is_valid(&Claim::new("bad verb", "x")));
+        assert!(!claim_is_valid(&Claim::new("", "x")));
+        assert!(!claim_is_valid(&Claim::new("read", "a..b")));
+        assert!(!claim_is_valid(&Claim::new("read", ".a")));
+    }
+
+    #[test]
+    fn invalid_list() {
+        let list = [
+            Claim::new("read", "a"),
+            Claim::new("read", "a."),
+            Claim::new("bad verb", "x"),
+        ];
+        assert_eq!(claims_invalid(list.iter()), vec![&list[1], &list[2]]);
+    }
+
+    #[test]
+    fn parse_list_owned() {
+        let strings: Vec<String> = ["read:b", "read:a"].iter().map(|s| s.to_string()).collect();
+        let expected = vec![Claim::new("read", "a"), Claim::new("read", "b")];
+
+        assert_eq!(claims_from_strs(strings.iter()), Ok(expected));
+        assert_eq!(
+            claims_from_strs("read:a read:*".split(' ')),
+            Ok(vec![Claim::new("read", ""), Claim::new("read", "a")])
+        );
+    }
+
     #[test]
     fn parse_list_blank() {
         let strings: Vec<&str> = Vec::new();
@@ -1,8 +1,10 @@
 use crate::claim::claim_from_str::claim_from_str;
+use crate::claim::invalid_query::parse_query;
 use crate::claim::Claim;
+use crate::error::Error;
 
 pub fn claim_direct_descendant_str(claim: &Claim, query: &str) -> Option<String> {
-    let parse_result = claim_from_str(query);
+    let parse_result = parse_query(query);
     if let Ok(parsed) = parse_result {
         claim_direct_descendant(claim, &parsed)
     } else {
@@ -10,6 +12,14 @@ pub fn claim_direct_descendant_str(claim: &Claim, query: &str) -> Option<String>
     }
 }
 
+pub fn try_claim_direct_descendant_str(
+    claim: &Claim,
+    query: &str,
+) -> Result<Option<String>, Error> {
+    let parsed = claim_from_str(query)?;
+    Ok(claim_direct_descendant(claim, &parsed))
+}
+
 pub fn claim_direct_descendant(claim: &Claim, query: &Claim) -> Option<String> {
     if claim.verb != query.verb || claim.is_global() {
         return None;
@@ -32,14 +42,14 @@ pub fn claim_direct_descendant(claim: &Claim, query: &Claim) -> Option<String> {
     let len = query.subject.len() + 1;
     let rest = &claim.subject[len..];
 
-    return match rest.find(".") {
+    match rest.find('.') {
         None => Some(String::from(rest)),
         Some(idx) => Some(String::from(&rest[..idx])),
-    };
+    }
 }
 
 pub fn claim_direct_child_str(claim: &Claim, query: &str) -> Option<String> {
-    let parse_result = claim_from_str(query);
+    let parse_result = parse_query(query);
     if let Ok(parsed) = parse_result {
         claim_direct_child(claim, &parsed)
     } else {
@@ -47,6 +57,11 @@ pub fn claim_direct_child_str(claim: &Claim, query: &str) -> Option<String> {
     }
 }
 
+pub fn try_claim_direct_child_str(claim: &Claim, query: &str) -> Result<Option<String>, Error> {
+    let parsed = claim_from_str(query)?;
+    Ok(claim_direct_child(claim, &parsed))
+}
+
 pub fn claim_direct_child(claim: &Claim, query: &Claim) -> Option<String> {
     if claim.verb != query.verb || claim.is_global() {
         return None;
@@ -70,18 +85,18 @@ pub fn claim_direct_child(claim: &Claim, query: &Claim) -> Option<String> {
     let len = query.subject.len() + 1;
     let rest = &claim.subject[len..];
 
-    return if rest.contains(".") {
+    if rest.contains('.') {
         None
     } else {
         Some(String::from(rest))
-    };
+    }
 }
 
 pub fn claims_direct_children_str<'a, I>(claims: I, query: &str) -> Vec<String>
 where
     I: Iterator<Item = &'a Claim>,
 {
-    let parse_result = claim_from_str(query);
+    let parse_result = parse_query(query);
     if let Ok(parsed) = parse_result {
         claims_direct_children(claims, &parsed)
     } else {
@@ -89,6 +104,14 @@ where
     }
 }
 
+pub fn try_claims_direct_children_str<'a, I>(claims: I, query: &str) -> Result<Vec<String>, Error>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let parsed = claim_from_str(query)?;
+    Ok(claims_direct_children(claims, &parsed))
+}
+
 pub fn claims_direct_children<'a, I>(claims: I, query: &Claim) -> Vec<String>
 where
     I: Iterator<Item = &'a Claim>,
@@ -106,7 +129,7 @@ pub fn claims_direct_descendants_str<'a, I>(claims: I, query: &str) -> Vec<Strin
 where
     I: Iterator<Item = &'a Claim>,
 {
-    let parse_result = claim_from_str(query);
+    let parse_result = parse_query(query);
     if let Ok(parsed) = parse_result {
         claims_direct_descendants(claims, &parsed)
     } else {
@@ -114,6 +137,17 @@ where
     }
 }
 
+pub fn try_claims_direct_descendants_str<'a, I>(
+    claims: I,
+    query: &str,
+) -> Result<Vec<String>, Error>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let parsed = claim_from_str(query)?;
+    Ok(claims_direct_descendants(claims, &parsed))
+}
+
 pub fn claims_direct_descendants<'a, I>(claims: I, query: &Claim) -> Vec<String>
 where
     I: Iterator<Item = &'a Claim>,
@@ -127,6 +161,96 @@ where
     vec
 }
 
+/// A subject under a query, `depth` segments below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Descendant {
+    pub subject: String,
+    pub depth: usize,
+}
+
+/// Limits the descendants returned by `claims_descendants`: at most
+/// `max_depth` segments below the query, skipping the first `offset` ones and
+/// returning at most `limit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DescendantsOptions {
+    pub max_depth: Option<usize>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+pub fn claims_descendants_str<'a, I>(
+    claims: I,
+    query: &str,
+    options: DescendantsOptions,
+) -> Vec<Descendant>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let parse_result = parse_query(query);
+    if let Ok(parsed) = parse_result {
+        claims_descendants(claims, &parsed, options)
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn try_claims_descendants_str<'a, I>(
+    claims: I,
+    query: &str,
+    options: DescendantsOptions,
+) -> Result<Vec<Descendant>, Error>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let parsed = claim_from_str(query)?;
+    Ok(claims_descendants(claims, &parsed, options))
+}
+
+/// Every subject under the query that leads to a claim of the list, with
+/// parents listed right before their descendants.
+pub fn claims_descendants<'a, I>(
+    claims: I,
+    query: &Claim,
+    options: DescendantsOptions,
+) -> Vec<Descendant>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let base_depth = if query.is_global() {
+        0
+    } else {
+        query.subject.split('.').count()
+    };
+
+    let mut vec: Vec<Descendant> = Vec::new();
+    for claim in claims {
+        if claim_direct_descendant(claim, query).is_none() {
+            continue;
+        }
+
+        let segments: Vec<&str> = claim.subject.split('.').collect();
+        let max = options
+            .max_depth
+            .map(|d| (base_depth + d).min(segments.len()))
+            .unwrap_or(segments.len());
+        for end in base_depth + 1..=max {
+            vec.push(Descendant {
+                subject: segments[..end].join("."),
+                depth: end - base_depth,
+            });
+        }
+    }
+
+    vec.sort_by(|a, b| a.subject.split('.').cmp(b.subject.split('.')));
+    vec.dedup();
+
+    let iter = vec.into_iter().skip(options.offset);
+    match options.limit {
+        Some(limit) => iter.take(limit).collect(),
+        None => iter.collect(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,6 +263,57 @@ mod tests {
         assert_eq!(claims_direct_children_str(claims.iter(), query), expected)
     }
 
+    #[test]
+    fn test_try_with_bad_query() {
+        let claim = Claim::new("read", "paco");
+        let claims = [Claim::new("read", "paco")];
+        let err = Error::Syntax("the given claim adminasdasda is not valid".to_string());
+        let query = "adminasdasda";
+
+        assert_eq!(
+            try_claim_direct_descendant_str(&claim, query),
+            Err(err.clone())
+        );
+        assert_eq!(try_claim_direct_child_str(&claim, query), Err(err.clone()));
+        assert_eq!(
+            try_claims_direct_children_str(claims.iter(), query),
+            Err(err.clone())
+        );
+        assert_eq!(
+            try_claims_direct_descendants_str(claims.iter(), query),
+            Err(err.clone())
+        );
+        assert_eq!(
+            try_claims_descendants_str(claims.iter(), query, DescendantsOptions::default()),
+            Err(err)
+        );
+    }
+
+    #[test]
+    fn test_try_with_good_query() {
+        let claim = Claim::new("read", "paco.and.more");
+        let claims = [Claim::new("read", "paco.and.more")];
+        let and = Some(String::from("and"));
+
+        assert_eq!(
+            try_claim_direct_descendant_str(&claim, "read:paco"),
+            Ok(and)
+        );
+        assert_eq!(try_claim_direct_child_str(&claim, "read:paco"), Ok(None));
+        assert_eq!(
+            try_claims_direct_children_str(claims.iter(), "read:paco.and"),
+            Ok(vec![String::from("more")])
+        );
+        assert_eq!(
+            try_claims_direct_descendants_str(claims.iter(), "read:paco"),
+            Ok(vec![String::from("and")])
+        );
+        assert_eq!(
+            try_claims_descendants_str(claims.iter(), "admin:*", DescendantsOptions::default()),
+            Ok(Vec::new())
+        );
+    }
+
     #[test]
     fn test_claims_direct_children_with_none() {
         let claims = [Claim::new("read", "paco"), Claim::new("read", "something")];
@@ -199,6 +374,105 @@ mod tests {
         )
     }
 
+    fn descendants(
+        claims: &[Claim],
+        query: &str,
+        options: DescendantsOptions,
+    ) -> Vec<(String, usize)> {
+        claims_descendants_str(claims.iter(), query, options)
+            .into_iter()
+            .map(|d| (d.subject, d.depth))
+            .collect()
+    }
+
+    fn sample() -> Vec<Claim> {
+        vec![
+            Claim::new("read", "paco.what"),
+            Claim::new("read", "paco.and.something"),
+            Claim::new("read", "paco.and.another"),
+            Claim::new("read", "paco-x.y"),
+            Claim::new("read", "paco"),
+            Claim::new("admin", "blah"),
+        ]
+    }
+
+    #[test]
+    fn test_claims_descendants_with_bad_query() {
+        let result = descendants(&sample(), "adminasdasda", DescendantsOptions::default());
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_claims_descendants_all() {
+        let expected = vec![
+            ("paco.and".to_string(), 1),
+            ("paco.and.another".to_string(), 2),
+            ("paco.and.something".to_string(), 2),
+            ("paco.what".to_string(), 1),
+        ];
+        assert_eq!(
+            descendants(&sample(), "read:paco", DescendantsOptions::default()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_claims_descendants_global() {
+        let expected = vec![
+            ("paco".to_string(), 1),
+            ("paco.and".to_string(), 2),
+            ("paco.and.another".to_string(), 3),
+            ("paco.and.something".to_string(), 3),
+            ("paco.what".to_string(), 2),
+            ("paco-x".to_string(), 1),
+            ("paco-x.y".to_string(), 2),
+        ];
+        assert_eq!(
+            descendants(&sample(), "read:*", DescendantsOptions::default()),
+            expected
+        );
+
+        let global = [Claim::new("read", "")];
+        assert!(descendants(&global, "read:*", DescendantsOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_claims_descendants_max_depth() {
+        let options = DescendantsOptions {
+            max_depth: Some(1),
+            ..DescendantsOptions::default()
+        };
+        let expected = vec![("paco".to_string(), 1), ("paco-x".to_string(), 1)];
+        assert_eq!(descendants(&sample(), "read:*", options), expected);
+
+        let options = DescendantsOptions {
+            max_depth: Some(0),
+            ..DescendantsOptions::default()
+        };
+        assert!(descendants(&sample(), "read:*", options).is_empty());
+    }
+
+    #[test]
+    fn test_claims_descendants_pagination() {
+        let options = DescendantsOptions {
+            max_depth: None,
+            offset: 2,
+            limit: Some(2),
+        };
+        let expected = vec![
+            ("paco.and.another".to_string(), 3),
+            ("paco.and.something".to_string(), 3),
+        ];
+        assert_eq!(descendants(&sample(), "read:*", options), expected);
+
+        let options = DescendantsOptions {
+            max_depth: None,
+            offset: 10,
+            limit: Some(2),
+        };
+        assert!(descendants(&sample(), "read:*", options).is_empty());
+    }
+
     #[test]
     fn test_direct_descendant_valid_global() {
         let claim = Claim::new("read", "paco");
@@ -0,0 +1,87 @@
+use crate::claim::claim_from_str::claim_from_str;
+use crate::claim::Claim;
+use crate::error::Error;
+use std::sync::RwLock;
+
+/// Called with the query and its parse error, see `set_invalid_query_hook`.
+pub type InvalidQueryHook = fn(&str, &Error);
+
+static INVALID_QUERY_HOOK: RwLock<Option<InvalidQueryHook>> = RwLock::new(None);
+
+/// Sets a hook called whenever a `_str` helper (`claim_check_str`,
+/// `claims_direct_children_str`, `ChainedCredential::check_str`,
+/// `Directory::principals_check_str`...) is given a query that is not a
+/// valid claim, e.g. to log or count them. `None` removes it.
+///
+/// The helpers still answer as if nothing matched (`false`, `None` or an
+/// empty list) and never panic. The hook is shared by the whole process; use
+/// the `try_` variants to handle the error of a single call instead.
+pub fn set_invalid_query_hook(hook: Option<InvalidQueryHook>) {
+    *INVALID_QUERY_HOOK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = hook;
+}
+
+pub fn invalid_query_hook() -> Option<InvalidQueryHook> {
+    *INVALID_QUERY_HOOK
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Parses a query for the `_str` helpers, calling the hook on errors.
+pub(crate) fn parse_query(query: &str) -> Result<Claim, Error> {
+    let result = claim_from_str(query);
+    if let Err(ref err) = result {
+        if let Some(hook) = invalid_query_hook() {
+            hook(query, err);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claim::claim_check_str;
+    use std::sync::Mutex;
+
+    static SEEN: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+    fn record(query: &str, err: &Error) {
+        SEEN.lock()
+            .unwrap()
+            .push((String::from(query), err.to_string()));
+    }
+
+    #[test]
+    fn test_hook() {
+        // other tests may run meanwhile, so only look for these queries
+        let seen = |query: &str| {
+            SEEN.lock()
+                .unwrap()
+                .iter()
+                .filter(|(q, _)| q == query)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let claim = Claim::new("read", "");
+
+        set_invalid_query_hook(Some(record));
+        assert!(invalid_query_hook().is_some());
+        assert!(!claim_check_str(&claim, "hook test query"));
+        assert!(claim_check_str(&claim, "read:hook-test"));
+        set_invalid_query_hook(None);
+        assert!(invalid_query_hook().is_none());
+        assert!(!claim_check_str(&claim, "hook test query 2"));
+
+        assert_eq!(
+            seen("hook test query"),
+            vec![(
+                "hook test query".to_string(),
+                "the given claim hook test query is not valid".to_string()
+            )]
+        );
+        assert!(seen("read:hook-test").is_empty());
+        assert!(seen("hook test query 2").is_empty());
+    }
+}
@@ -10,12 +10,12 @@ mod tests {
 
     fn check_valid(claim: &str) {
         let msg = format!("claim: '{}' should work", claim);
-        assert!(is_valid_claim_str(claim), msg)
+        assert!(is_valid_claim_str(claim), "{}", msg)
     }
 
     fn check_invalid(claim: &str) {
         let msg = format!("claim: '{}' should fail", claim);
-        assert!(!is_valid_claim_str(claim), msg)
+        assert!(!is_valid_claim_str(claim), "{}", msg)
     }
 
     #[test]
@@ -0,0 +1,180 @@
+use crate::claim::claim_from_str::{claim_from_str, err_not_parsed};
+use crate::claim::Claim;
+use crate::error::Error;
+use std::fmt;
+
+/// Options of `claim_from_str_lenient`. Whitespace trimming and dot
+/// collapsing are always applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LenientOptions {
+    /// Lowercase the verb, e.g. `Read:projects` gives `read:projects`.
+    pub lowercase_verb: bool,
+}
+
+/// A change `claim_from_str_lenient` made to its input before parsing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// Leading or trailing whitespace was removed.
+    TrimmedWhitespace,
+    /// The verb had uppercase characters.
+    LowercasedVerb,
+    /// Repeated dots in the subject were collapsed into one, e.g. `a..b`.
+    CollapsedDots,
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Normalization::TrimmedWhitespace => write!(f, "trimmed whitespace"),
+            Normalization::LowercasedVerb => write!(f, "lowercased verb"),
+            Normalization::CollapsedDots => write!(f, "collapsed repeated dots"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LenientClaim {
+    pub claim: Claim,
+    /// In the order they were applied, empty if the input needed none.
+    pub normalizations: Vec<Normalization>,
+}
+
+/// Parses user-entered claims, normalizing the input first. Meant for forms
+/// and imports; `claim_from_str` stays strict. The error, if any, refers to
+/// the original input.
+pub fn claim_from_str_lenient(
+    claim_str: &str,
+    options: &LenientOptions,
+) -> Result<LenientClaim, Error> {
+    let mut normalizations = Vec::new();
+
+    let trimmed = claim_str.trim();
+    if trimmed.len() != claim_str.len() {
+        normalizations.push(Normalization::TrimmedWhitespace);
+    }
+
+    let (verb, subject) = match trimmed.split_once(':') {
+        Some(parts) => parts,
+        None => (trimmed, ""),
+    };
+
+    let mut verb = String::from(verb);
+    if options.lowercase_verb && verb.chars().any(char::is_uppercase) {
+        verb = verb.to_lowercase();
+        normalizations.push(Normalization::LowercasedVerb);
+    }
+
+    let mut subject = String::from(subject);
+    if subject.contains("..") {
+        while subject.contains("..") {
+            subject = subject.replace("..", ".");
+        }
+        normalizations.push(Normalization::CollapsedDots);
+    }
+
+    let normalized = if trimmed.contains(':') {
+        format!("{}:{}", verb, subject)
+    } else {
+        verb
+    };
+    claim_from_str(&normalized)
+        .map(|claim| LenientClaim {
+            claim,
+            normalizations,
+        })
+        .map_err(|_| err_not_parsed(claim_str))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lenient(claim_str: &str, lowercase_verb: bool) -> Result<LenientClaim, Error> {
+        claim_from_str_lenient(claim_str, &LenientOptions { lowercase_verb })
+    }
+
+    #[test]
+    fn test_valid_input_is_untouched() {
+        for claim_str in ["read:*", "read:a.b", "read:a.*", "Read:a"].iter() {
+            let result = lenient(claim_str, false).unwrap();
+            assert_eq!(result.claim, claim_from_str(claim_str).unwrap());
+            assert!(result.normalizations.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_trim() {
+        for claim_str in [" admin:stuff", "admin:stuff ", "\tadmin:stuff\n"].iter() {
+            let result = lenient(claim_str, false).unwrap();
+            assert_eq!(result.claim, Claim::new("admin", "stuff"));
+            assert_eq!(
+                result.normalizations,
+                vec![Normalization::TrimmedWhitespace]
+            );
+        }
+    }
+
+    #[test]
+    fn test_lowercase_verb() {
+        let result = lenient("ReAd:Projects", true).unwrap();
+        assert_eq!(result.claim, Claim::new("read", "Projects"));
+        assert_eq!(result.normalizations, vec![Normalization::LowercasedVerb]);
+
+        let result = lenient("ReAd:Projects", false).unwrap();
+        assert_eq!(result.claim, Claim::new("ReAd", "Projects"));
+        assert!(result.normalizations.is_empty());
+    }
+
+    #[test]
+    fn test_collapse_dots() {
+        let result = lenient("read:a...b..c..*", false).unwrap();
+        assert_eq!(result.claim, Claim::new("read", "a.b.c"));
+        assert_eq!(result.normalizations, vec![Normalization::CollapsedDots]);
+    }
+
+    #[test]
+    fn test_all_normalizations() {
+        let result = lenient("  WRITE:a..b ", true).unwrap();
+        assert_eq!(result.claim, Claim::new("write", "a.b"));
+        assert_eq!(
+            result.normalizations,
+            vec![
+                Normalization::TrimmedWhitespace,
+                Normalization::LowercasedVerb,
+                Normalization::CollapsedDots,
+            ]
+        );
+        let descriptions: Vec<String> = result
+            .normalizations
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                "trimmed whitespace",
+                "lowercased verb",
+                "collapsed repeated dots"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_still_invalid() {
+        for claim_str in [" noverb ", "read:a b", "read:..a", "read:**", ""].iter() {
+            assert_eq!(
+                lenient(claim_str, true),
+                Err(Error::Syntax(format!(
+                    "the given claim {} is not valid",
+                    claim_str
+                )))
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_stays_strict() {
+        assert!(claim_from_str(" admin:stuff").is_err());
+        assert!(claim_from_str("read:a..b").is_err());
+    }
+}
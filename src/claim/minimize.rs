@@ -0,0 +1,58 @@
+use crate::claim::check::claim_check;
+use crate::claim::Claim;
+
+/// Returns the smallest list of claims covering the same queries as the given
+/// ones: sorted, deduplicated and without claims covered by another one.
+pub fn claims_minimize<'a, I>(claims: I) -> Vec<Claim>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let mut list: Vec<&Claim> = claims.collect();
+    list.sort();
+    list.dedup();
+
+    list.iter()
+        .filter(|c| !list.iter().any(|o| o != *c && claim_check(o, c)))
+        .map(|c| (*c).clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{claims, strings};
+
+    fn minimize(list: &[&str]) -> Vec<String> {
+        strings(&claims_minimize(claims(list).iter()))
+    }
+
+    #[test]
+    fn test_minimize_blank() {
+        let expected: Vec<String> = Vec::new();
+        assert_eq!(minimize(&[]), expected);
+    }
+
+    #[test]
+    fn test_minimize_removes_covered() {
+        assert_eq!(
+            minimize(&[
+                "read:a.b",
+                "read:a",
+                "read:a-b",
+                "read:a.b.c",
+                "admin:x",
+                "admin:*",
+                "write:a.b",
+            ]),
+            vec!["admin:*", "read:a", "read:a-b", "write:a.b"]
+        );
+    }
+
+    #[test]
+    fn test_minimize_keeps_siblings() {
+        assert_eq!(
+            minimize(&["read:a.b", "read:a.c", "read:a.b"]),
+            vec!["read:a.b", "read:a.c"]
+        );
+    }
+}
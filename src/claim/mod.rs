@@ -1,30 +1,68 @@
 mod check;
 mod claim_from_str;
 mod descendants;
+mod invalid_query;
 mod is_valid_claim_str;
-
-use crate::claim::check::{claim_check, claim_check_str, claim_exact, claim_exact_str};
-use crate::claim::claim_from_str::claims_from_strs;
-use crate::claim::descendants::{
+mod lenient;
+mod minimize;
+mod ordering;
+mod reader;
+mod segments;
+
+pub use crate::claim::check::{
+    claim_check, claim_check_parts, claim_check_str, claim_exact, claim_exact_str,
+    try_claim_check_str, try_claim_exact_str,
+};
+pub use crate::claim::claim_from_str::{
+    claim_from_parts, claim_from_str, claim_is_valid, claims_from_strs,
+    claims_from_strs_all_errors, claims_invalid, ClaimParseError,
+};
+pub use crate::claim::descendants::{
     claim_direct_child, claim_direct_child_str, claim_direct_descendant,
-    claim_direct_descendant_str,
+    claim_direct_descendant_str, claims_descendants, claims_descendants_str,
+    claims_direct_children, claims_direct_children_str, claims_direct_descendants,
+    claims_direct_descendants_str, try_claim_direct_child_str, try_claim_direct_descendant_str,
+    try_claims_descendants_str, try_claims_direct_children_str, try_claims_direct_descendants_str,
+    Descendant, DescendantsOptions,
+};
+pub(crate) use crate::claim::invalid_query::parse_query;
+pub use crate::claim::invalid_query::{
+    invalid_query_hook, set_invalid_query_hook, InvalidQueryHook,
+};
+pub use crate::claim::is_valid_claim_str::is_valid_claim_str;
+pub use crate::claim::lenient::{
+    claim_from_str_lenient, LenientClaim, LenientOptions, Normalization,
+};
+pub use crate::claim::minimize::claims_minimize;
+pub use crate::claim::ordering::{claims_check_sorted, claims_covered_range};
+pub use crate::claim::reader::{read_claims_minimized, read_claims_sorted, ClaimReader};
+pub use crate::claim::segments::{
+    claim_ancestors, claim_child, claim_depth, claim_is_ancestor_of, claim_parent, claim_segments,
+    Ancestors, Segments,
 };
 use crate::error::Error;
-use claim_from_str::claim_from_str;
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
+use std::str::FromStr;
 
 lazy_static! {
     static ref CLAIM_REGEX: Regex =
         Regex::new(r"^([\w_\-]+):(\*|(\w[\w_.\-]*)(\.|\.\*)?)$").unwrap();
+    static ref WORD_CHAR_REGEX: Regex = Regex::new(r"^\w$").unwrap();
+}
+
+/// Whether the character is a word character (`\w`) of the claim grammar.
+pub(crate) fn is_word_char(c: char) -> bool {
+    let mut buf = [0; 4];
+    WORD_CHAR_REGEX.is_match(c.encode_utf8(&mut buf))
 }
 
-#[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Claim {
-    verb: String,
-    subject: String,
+    pub(crate) verb: String,
+    pub(crate) subject: String,
 }
 
 impl Claim {
@@ -42,68 +80,137 @@ impl Claim {
         }
     }
 
+    /// Like `new`, but fails if the claim would not be valid and normalizes
+    /// the subject like `parse` does.
+    pub fn try_new(verb: &str, subject: &str) -> Result<Self, Error> {
+        claim_from_parts(verb, subject)
+    }
+
+    pub fn try_from_tuple(tuple: (&str, &str)) -> Result<Self, Error> {
+        claim_from_parts(tuple.0, tuple.1)
+    }
+
     pub fn parse(string: &str) -> Result<Self, Error> {
         claim_from_str(string)
     }
 
-    pub fn parse_list<'a, I>(claim_strs: I) -> Result<Vec<Claim>, Error>
+    pub fn parse_list<I, S>(claim_strs: I) -> Result<Vec<Claim>, Error>
     where
-        I: Iterator<Item = &'a &'a str>,
+        I: Iterator<Item = S>,
+        S: AsRef<str>,
     {
         claims_from_strs(claim_strs)
     }
 
+    pub fn parse_list_all_errors<I, S>(claim_strs: I) -> Result<Vec<Claim>, Vec<ClaimParseError>>
+    where
+        I: Iterator<Item = S>,
+        S: AsRef<str>,
+    {
+        claims_from_strs_all_errors(claim_strs)
+    }
+
     // INSTANCE METHODS
 
-    pub fn cmp(&self, other: &Self) -> Ordering {
-        let verb_comp = self.verb.cmp(&other.verb);
-        if verb_comp == Ordering::Equal {
-            self.subject.cmp(&other.subject)
-        } else {
-            verb_comp
-        }
+    pub fn verb(&self) -> &str {
+        &self.verb
+    }
+
+    /// Empty for global claims.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// False for claims built with `new` or `from_tuple` that `parse` would
+    /// have rejected or normalized differently.
+    pub fn is_valid(&self) -> bool {
+        claim_is_valid(self)
     }
 
     pub fn is_global(&self) -> bool {
-        self.subject.len() == 0
+        self.subject.is_empty()
+    }
+
+    pub fn segments(&self) -> Segments<'_> {
+        claim_segments(self)
+    }
+
+    pub fn depth(&self) -> usize {
+        claim_depth(self)
+    }
+
+    pub fn parent(&self) -> Option<Claim> {
+        claim_parent(self)
+    }
+
+    pub fn ancestors(&self) -> Ancestors<'_> {
+        claim_ancestors(self)
+    }
+
+    pub fn child(&self, segment: &str) -> Result<Claim, Error> {
+        claim_child(self, segment)
+    }
+
+    pub fn is_ancestor_of(&self, other: &Claim) -> bool {
+        claim_is_ancestor_of(self, other)
+    }
+
+    pub fn is_descendant_of(&self, other: &Claim) -> bool {
+        claim_is_ancestor_of(other, self)
     }
 
     pub fn is_exact(&self, query: &Claim) -> bool {
-        claim_exact(&self, query)
+        claim_exact(self, query)
     }
 
     pub fn is_exact_str(&self, query: &str) -> bool {
-        claim_exact_str(&self, query)
+        claim_exact_str(self, query)
+    }
+
+    pub fn try_is_exact_str(&self, query: &str) -> Result<bool, Error> {
+        try_claim_exact_str(self, query)
     }
 
     pub fn check(&self, query: &Claim) -> bool {
-        claim_check(&self, query)
+        claim_check(self, query)
     }
 
     pub fn check_str(&self, query: &str) -> bool {
-        claim_check_str(&self, query)
+        claim_check_str(self, query)
+    }
+
+    pub fn try_check_str(&self, query: &str) -> Result<bool, Error> {
+        try_claim_check_str(self, query)
     }
 
     pub fn direct_child(&self, query: &Claim) -> Option<String> {
-        claim_direct_child(self, &query)
+        claim_direct_child(self, query)
     }
 
     pub fn direct_child_str(&self, query: &str) -> Option<String> {
-        claim_direct_child_str(self, &query)
+        claim_direct_child_str(self, query)
+    }
+
+    pub fn try_direct_child_str(&self, query: &str) -> Result<Option<String>, Error> {
+        try_claim_direct_child_str(self, query)
     }
 
     pub fn direct_descendant(&self, query: &Claim) -> Option<String> {
-        claim_direct_descendant(self, &query)
+        claim_direct_descendant(self, query)
     }
 
     pub fn direct_descendant_str(&self, query: &str) -> Option<String> {
-        claim_direct_descendant_str(self, &query)
+        claim_direct_descendant_str(self, query)
+    }
+
+    pub fn try_direct_descendant_str(&self, query: &str) -> Result<Option<String>, Error> {
+        try_claim_direct_descendant_str(self, query)
     }
 }
 
 impl fmt::Display for Claim {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let sub = if self.subject == "" {
+        let sub = if self.subject.is_empty() {
             "*"
         } else {
             &self.subject
@@ -112,9 +219,40 @@ impl fmt::Display for Claim {
     }
 }
 
+impl FromStr for Claim {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        claim_from_str(s)
+    }
+}
+
+impl TryFrom<&str> for Claim {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        claim_from_str(value)
+    }
+}
+
+impl TryFrom<String> for Claim {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        claim_from_str(&value)
+    }
+}
+
+impl From<Claim> for String {
+    fn from(claim: Claim) -> Self {
+        claim.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     fn make(verb: &str, subject: &str) -> Claim {
         Claim::new(verb, subject)
@@ -135,6 +273,71 @@ mod tests {
         assert!(!make("read", "paco").is_global());
     }
 
+    #[test]
+    fn test_accessors() {
+        let claim = make("read", "a.b");
+        assert_eq!(claim.verb(), "read");
+        assert_eq!(claim.subject(), "a.b");
+        assert_eq!(make("read", "").subject(), "");
+    }
+
+    #[test]
+    fn test_segments() {
+        let claim = make("read", "a.b.c");
+        assert_eq!(claim.segments().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(claim.depth(), 3);
+        assert_eq!(claim.parent(), Some(make("read", "a.b")));
+        assert_eq!(
+            claim.ancestors().collect::<Vec<_>>(),
+            vec![make("read", "a.b"), make("read", "a"), make("read", "")]
+        );
+        assert_eq!(claim.child("d"), Ok(make("read", "a.b.c.d")));
+        assert!(claim.child("d.e").is_err());
+    }
+
+    #[test]
+    fn test_is_ancestor_of() {
+        let global = make("read", "");
+        let a_b = make("read", "a.b");
+
+        assert!(global.is_ancestor_of(&a_b));
+        assert!(!a_b.is_ancestor_of(&global));
+        assert!(a_b.is_descendant_of(&global));
+        assert!(!global.is_descendant_of(&a_b));
+        assert!(!a_b.is_descendant_of(&a_b));
+
+        for ancestor in a_b.ancestors() {
+            assert!(ancestor.is_ancestor_of(&a_b));
+            assert!(ancestor.check(&a_b));
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Claim::try_new("read", "a.*"), Ok(make("read", "a")));
+        assert_eq!(Claim::try_new("read", "*"), Ok(make("read", "")));
+        assert_eq!(
+            Claim::try_from_tuple(("read", "a.b.")),
+            Ok(make("read", "a.b"))
+        );
+        assert_eq!(
+            Claim::try_new("bad verb", "x"),
+            Err(Error::Syntax(
+                "the given claim bad verb:x is not valid".to_string()
+            ))
+        );
+        assert!(Claim::try_from_tuple(("read", ".a")).is_err());
+        assert!(Claim::try_new("read", "a..").is_err());
+        assert!(Claim::parse("read:a..").is_err());
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(make("read", "a").is_valid());
+        assert!(!make("read", "a.").is_valid());
+        assert!(!make("bad verb", "x").is_valid());
+    }
+
     #[test]
     fn test_parse() {
         assert_eq!(Claim::parse("read:*"), Ok(make("read", "")));
@@ -157,6 +360,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_word_char() {
+        for c in ['a', 'Z', '0', '_', 'ñ', 'é', '٣'].iter() {
+            assert!(is_word_char(*c), "{}", c);
+        }
+        for c in ['-', '.', ' ', '%', '²', '½', '*'].iter() {
+            assert!(!is_word_char(*c), "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_parse_list_all_errors() {
+        assert_eq!(
+            Claim::parse_list_all_errors(["read:b", "read:a", "read:b"].iter()),
+            Ok(vec![make("read", "a"), make("read", "b")])
+        );
+
+        let errors = Claim::parse_list_all_errors(["bad", "read:a", "worse"].iter()).unwrap_err();
+        let indices: Vec<usize> = errors.iter().map(|e| e.index).collect();
+        assert_eq!(indices, vec![0, 2]);
+    }
+
     #[test]
     fn test_parse_list() {
         assert_eq!(
@@ -182,6 +407,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_list_owned() {
+        let strings: Vec<String> = vec!["read:b".to_string(), "read:a.*".to_string()];
+        assert_eq!(
+            Claim::parse_list(strings.iter()),
+            Ok(vec![make("read", "a"), make("read", "b")])
+        );
+        assert_eq!(
+            Claim::parse_list(strings.into_iter()),
+            Ok(vec![make("read", "a"), make("read", "b")])
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("read:*".parse::<Claim>(), Ok(make("read", "")));
+        assert_eq!("read:a.b.*".parse::<Claim>(), Ok(make("read", "a.b")));
+        assert!("bad-stuff.*".parse::<Claim>().is_err());
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(Claim::try_from("read:a"), Ok(make("read", "a")));
+        assert_eq!(
+            Claim::try_from(String::from("read:a.")),
+            Ok(make("read", "a"))
+        );
+        assert!(Claim::try_from("nope").is_err());
+        assert!(Claim::try_from(String::from("nope")).is_err());
+    }
+
+    #[test]
+    fn test_into_string() {
+        assert_eq!(String::from(make("read", "")), "read:*");
+        let s: String = make("read", "a.b").into();
+        assert_eq!(s, "read:a.b");
+    }
+
+    #[test]
+    fn test_clone_and_hash() {
+        let claim = make("read", "a");
+        let cloned = claim.clone();
+        assert_eq!(claim, cloned);
+
+        let mut map = HashMap::new();
+        map.insert(claim, 1);
+        map.insert(make("read", "b"), 2);
+        assert_eq!(map.get(&cloned), Some(&1));
+        assert_eq!(map.get(&make("read", "c")), None);
+    }
+
     #[test]
     fn test_cmp() {
         let admin_a = make("admin", "a");
@@ -196,6 +472,9 @@ mod tests {
 
         let is_eq = admin_b == make("admin", "b");
         assert!(is_eq);
+
+        assert!(make("read", "a") < make("read", "a.b"));
+        assert!(make("read", "a.b") < make("read", "a-b"));
     }
 
     #[test]
@@ -266,6 +545,26 @@ mod tests {
         assert!(!read_a.check_str("admin:*"));
     }
 
+    #[test]
+    fn test_try_str() {
+        let claim = make("admin", "paco.x");
+        assert_eq!(claim.try_check_str("admin:paco.x.y"), Ok(true));
+        assert_eq!(claim.try_is_exact_str("admin:paco.x"), Ok(true));
+        assert_eq!(
+            claim.try_direct_child_str("admin:paco"),
+            Ok(Some("x".to_string()))
+        );
+        assert_eq!(
+            claim.try_direct_descendant_str("admin:*"),
+            Ok(Some("paco".to_string()))
+        );
+
+        assert!(claim.try_check_str("admin").is_err());
+        assert!(claim.try_is_exact_str("admin").is_err());
+        assert!(claim.try_direct_child_str("admin").is_err());
+        assert!(claim.try_direct_descendant_str("admin").is_err());
+    }
+
     #[test]
     fn test_direct_child() {
         let claim_global = make("admin", "");
@@ -0,0 +1,130 @@
+use crate::claim::check::claim_check;
+use crate::claim::segments::claim_segments;
+use crate::claim::Claim;
+use std::cmp::Ordering;
+
+/// Claims are ordered by verb and then by subject segment by segment, so
+/// that `read:a` < `read:a.b` < `read:a-b`: every claim is immediately
+/// followed by its descendants.
+impl Ord for Claim {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.verb
+            .cmp(&other.verb)
+            .then_with(|| claim_segments(self).cmp(claim_segments(other)))
+    }
+}
+
+impl PartialOrd for Claim {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Given a sorted list, returns the slice of claims covered by `claim` (the
+/// claim itself, if present, and all its descendants).
+pub fn claims_covered_range<'a>(sorted: &'a [Claim], claim: &Claim) -> &'a [Claim] {
+    let start = sorted.partition_point(|c| c < claim);
+    let len = sorted[start..].partition_point(|c| claim_check(claim, c));
+    &sorted[start..start + len]
+}
+
+/// Like checking every claim of a sorted list against the query, but only
+/// looking up the query and its ancestors.
+pub fn claims_check_sorted(sorted: &[Claim], query: &Claim) -> bool {
+    sorted.binary_search(query).is_ok()
+        || query
+            .ancestors()
+            .any(|ancestor| sorted.binary_search(&ancestor).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{claims, strings};
+
+    #[test]
+    fn test_order_keeps_descendants_together() {
+        let mut list = vec![
+            Claim::new("read", "a-b"),
+            Claim::new("read", "a.b"),
+            Claim::new("read", "a"),
+            Claim::new("read", "a.b.c"),
+            Claim::new("read", ""),
+            Claim::new("read", "a.a"),
+            Claim::new("admin", "z"),
+        ];
+        list.sort();
+        assert_eq!(
+            strings(&list),
+            vec![
+                "admin:z",
+                "read:*",
+                "read:a",
+                "read:a.a",
+                "read:a.b",
+                "read:a.b.c",
+                "read:a-b"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_order_consistent_with_eq() {
+        let a = Claim::new("read", "a.b");
+        assert_eq!(a.cmp(&Claim::new("read", "a.b")), Ordering::Equal);
+        assert!(Claim::new("read", "") < Claim::new("read", "a"));
+        assert!(Claim::new("admin", "z") < Claim::new("read", ""));
+    }
+
+    #[test]
+    fn test_covered_range() {
+        let list = claims(&[
+            "read:a",
+            "read:a.b",
+            "read:a.b.c",
+            "read:a-b",
+            "read:b",
+            "write:a.x",
+        ]);
+
+        assert_eq!(
+            strings(claims_covered_range(&list, &Claim::new("read", "a"))),
+            vec!["read:a", "read:a.b", "read:a.b.c"]
+        );
+        assert_eq!(
+            strings(claims_covered_range(&list, &Claim::new("read", ""))),
+            vec!["read:a", "read:a.b", "read:a.b.c", "read:a-b", "read:b"]
+        );
+        assert_eq!(
+            strings(claims_covered_range(&list, &Claim::new("write", "a"))),
+            vec!["write:a.x"]
+        );
+        assert!(claims_covered_range(&list, &Claim::new("read", "c")).is_empty());
+        assert!(claims_covered_range(&[], &Claim::new("read", "c")).is_empty());
+    }
+
+    #[test]
+    fn test_check_sorted() {
+        let list = claims(&["admin:*", "read:a.b", "read:c"]);
+        let queries = [
+            "admin:*",
+            "admin:x.y",
+            "read:a.b",
+            "read:a.b.c",
+            "read:a",
+            "read:a-b",
+            "read:c.d",
+            "read:*",
+            "write:a",
+        ];
+        for query in queries.iter() {
+            let query = Claim::parse(query).unwrap();
+            assert_eq!(
+                claims_check_sorted(&list, &query),
+                list.iter().any(|c| c.check(&query)),
+                "{}",
+                query
+            );
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::claim::check::claim_check;
+use crate::claim::claim_from_str::claim_from_str;
+use crate::claim::Claim;
+use crate::error::Error;
+use std::collections::BTreeSet;
+use std::io::BufRead;
+
+/// Reads claims from newline-delimited text, one per line, without keeping
+/// more than the current line in memory. Blank lines and lines starting with
+/// `#` (after leading whitespace) are skipped.
+///
+/// Errors carry the 1-based line number, e.g. `line 3: the given claim x is
+/// not valid`. Reading stops after an I/O error but goes on after an invalid
+/// line.
+pub struct ClaimReader<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+    failed: bool,
+}
+
+impl<R: BufRead> ClaimReader<R> {
+    pub fn new(reader: R) -> Self {
+        ClaimReader {
+            reader,
+            buf: String::new(),
+            line: 0,
+            failed: false,
+        }
+    }
+
+    /// The number of the last line read.
+    pub fn line_number(&self) -> usize {
+        self.line
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for ClaimReader<R> {
+    type Item = Result<Claim, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(Error::Io(format!("line {}: {}", self.line + 1, err))));
+                }
+            }
+
+            let line = self.buf.trim_end_matches(['\n', '\r']);
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let line_number = self.line;
+            return Some(
+                claim_from_str(line)
+                    .map_err(|err| Error::Syntax(format!("line {}: {}", line_number, err))),
+            );
+        }
+        None
+    }
+}
+
+/// Reads all the claims into a sorted and deduplicated list, like
+/// `claims_from_strs`, failing on the first error.
+pub fn read_claims_sorted<R: BufRead>(reader: R) -> Result<Vec<Claim>, Error> {
+    let set: BTreeSet<Claim> = ClaimReader::new(reader).collect::<Result<_, _>>()?;
+    Ok(set.into_iter().collect())
+}
+
+/// Reads all the claims into a minimized list, like `claims_minimize`,
+/// failing on the first error.
+pub fn read_claims_minimized<R: BufRead>(reader: R) -> Result<Vec<Claim>, Error> {
+    let set: BTreeSet<Claim> = ClaimReader::new(reader).collect::<Result<_, _>>()?;
+
+    // sorted claims come right before their descendants, so only the last
+    // kept claim can cover the next one
+    let mut minimized: Vec<Claim> = Vec::new();
+    for claim in set {
+        match minimized.last() {
+            Some(last) if claim_check(last, &claim) => {}
+            _ => minimized.push(claim),
+        }
+    }
+    Ok(minimized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claim::claims_minimize;
+    use crate::test_util::strings;
+    use std::io::{self, BufReader, Read};
+
+    const DUMP: &str = "\
+# exported claims
+read:projects.1
+
+read:projects
+  # indented comment
+write:*\r
+read:projects.1.settings
+read:projects
+";
+
+    #[test]
+    fn test_reader() {
+        let mut reader = ClaimReader::new(DUMP.as_bytes());
+        assert_eq!(reader.next(), Some(Ok(Claim::new("read", "projects.1"))));
+        assert_eq!(reader.line_number(), 2);
+
+        let rest: Vec<Claim> = reader.by_ref().map(|c| c.unwrap()).collect();
+        assert_eq!(
+            strings(&rest),
+            vec![
+                "read:projects",
+                "write:*",
+                "read:projects.1.settings",
+                "read:projects"
+            ]
+        );
+        assert_eq!(reader.line_number(), 8);
+    }
+
+    #[test]
+    fn test_reader_errors() {
+        let input = "read:a\nnot a claim\n\nread:b \nread:c";
+        let results: Vec<Result<Claim, Error>> = ClaimReader::new(input.as_bytes()).collect();
+        assert_eq!(
+            results,
+            vec![
+                Ok(Claim::new("read", "a")),
+                Err(Error::Syntax(
+                    "line 2: the given claim not a claim is not valid".to_string()
+                )),
+                Err(Error::Syntax(
+                    "line 4: the given claim read:b  is not valid".to_string()
+                )),
+                Ok(Claim::new("read", "c")),
+            ]
+        );
+    }
+
+    struct FailingRead;
+
+    impl Read for FailingRead {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk on fire"))
+        }
+    }
+
+    #[test]
+    fn test_reader_io_error() {
+        let input = "read:a\n".as_bytes().chain(BufReader::new(FailingRead));
+        let results: Vec<Result<Claim, Error>> = ClaimReader::new(input).collect();
+        assert_eq!(
+            results,
+            vec![
+                Ok(Claim::new("read", "a")),
+                Err(Error::Io("line 2: disk on fire".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_sorted() {
+        assert_eq!(
+            strings(&read_claims_sorted(DUMP.as_bytes()).unwrap()),
+            vec![
+                "read:projects",
+                "read:projects.1",
+                "read:projects.1.settings",
+                "write:*"
+            ]
+        );
+        assert_eq!(
+            read_claims_sorted("read:a\nread:".as_bytes()),
+            Err(Error::Syntax(
+                "line 2: the given claim read: is not valid".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_read_minimized() {
+        assert_eq!(
+            strings(&read_claims_minimized(DUMP.as_bytes()).unwrap()),
+            vec!["read:projects", "write:*"]
+        );
+
+        let input = "read:a-b\nread:a.b\nadmin:x\nread:a\nread:*\nadmin:x.y\nadmin:w";
+        let claims = read_claims_sorted(input.as_bytes()).unwrap();
+        assert_eq!(
+            read_claims_minimized(input.as_bytes()).unwrap(),
+            claims_minimize(claims.iter())
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use crate::claim::check::{claim_check, claim_exact};
+use crate::claim::{is_word_char, Claim};
+use crate::error::Error;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref SEGMENT_REGEX: Regex = Regex::new(r"^[\w_\-]+$").unwrap();
+}
+
+/// Iterator over the segments of a claim subject, see `Claim::segments`.
+#[derive(Debug, Clone)]
+pub struct Segments<'a> {
+    inner: Option<std::str::Split<'a, char>>,
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next()
+    }
+}
+
+/// Iterator over the ancestors of a claim, see `Claim::ancestors`.
+#[derive(Debug, Clone)]
+pub struct Ancestors<'a> {
+    verb: &'a str,
+    subject: Option<&'a str>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = Claim;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let subject = self.subject?;
+        let parent = parent_subject(subject);
+        self.subject = parent;
+        parent.map(|p| Claim::new(self.verb, p))
+    }
+}
+
+pub fn claim_segments(claim: &Claim) -> Segments<'_> {
+    let inner = if claim.is_global() {
+        None
+    } else {
+        Some(claim.subject.split('.'))
+    };
+    Segments { inner }
+}
+
+pub fn claim_depth(claim: &Claim) -> usize {
+    claim_segments(claim).count()
+}
+
+pub fn claim_parent(claim: &Claim) -> Option<Claim> {
+    parent_subject(&claim.subject).map(|p| Claim::new(&claim.verb, p))
+}
+
+pub fn claim_ancestors(claim: &Claim) -> Ancestors<'_> {
+    Ancestors {
+        verb: &claim.verb,
+        subject: Some(&claim.subject),
+    }
+}
+
+pub fn claim_child(claim: &Claim, segment: &str) -> Result<Claim, Error> {
+    let starts_with_word = segment.chars().next().map(is_word_char).unwrap_or(false);
+    if !SEGMENT_REGEX.is_match(segment) || (claim.is_global() && !starts_with_word) {
+        return Err(Error::Syntax(format!(
+            "the given segment {} is not valid",
+            segment
+        )));
+    }
+
+    if claim.is_global() {
+        Ok(Claim::new(&claim.verb, segment))
+    } else {
+        Ok(Claim::new(
+            &claim.verb,
+            &format!("{}.{}", claim.subject, segment),
+        ))
+    }
+}
+
+/// Strict version of `claim_check`: the claim covers the other one and is
+/// not the same claim.
+pub fn claim_is_ancestor_of(claim: &Claim, other: &Claim) -> bool {
+    claim_check(claim, other) && !claim_exact(claim, other)
+}
+
+fn parent_subject(subject: &str) -> Option<&str> {
+    if subject.is_empty() {
+        return None;
+    }
+
+    match subject.rfind('.') {
+        Some(idx) => Some(&subject[..idx]),
+        None => Some(""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let claim = Claim::new("read", "a.b-c.d");
+        let segments: Vec<&str> = claim_segments(&claim).collect();
+        assert_eq!(segments, vec!["a", "b-c", "d"]);
+        assert_eq!(claim_segments(&Claim::new("read", "")).count(), 0);
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(claim_depth(&Claim::new("read", "")), 0);
+        assert_eq!(claim_depth(&Claim::new("read", "a")), 1);
+        assert_eq!(claim_depth(&Claim::new("read", "a.b.c")), 3);
+    }
+
+    #[test]
+    fn test_parent() {
+        assert_eq!(
+            claim_parent(&Claim::new("read", "a.b")),
+            Some(Claim::new("read", "a"))
+        );
+        assert_eq!(
+            claim_parent(&Claim::new("read", "a")),
+            Some(Claim::new("read", ""))
+        );
+        assert_eq!(claim_parent(&Claim::new("read", "")), None);
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let ancestors: Vec<String> = claim_ancestors(&Claim::new("read", "a.b.c"))
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(ancestors, vec!["read:a.b", "read:a", "read:*"]);
+        assert_eq!(claim_ancestors(&Claim::new("read", "")).count(), 0);
+    }
+
+    #[test]
+    fn test_child() {
+        assert_eq!(
+            claim_child(&Claim::new("read", ""), "a"),
+            Ok(Claim::new("read", "a"))
+        );
+        assert_eq!(
+            claim_child(&Claim::new("read", "a"), "b-c"),
+            Ok(Claim::new("read", "a.b-c"))
+        );
+        assert_eq!(
+            claim_child(&Claim::new("read", "a"), "-b"),
+            Ok(Claim::new("read", "a.-b"))
+        );
+
+        let invalid = ["", "a.b", "*", "a b", "a:b"];
+        for segment in invalid.iter() {
+            assert_eq!(
+                claim_child(&Claim::new("read", "a"), segment),
+                Err(Error::Syntax(format!(
+                    "the given segment {} is not valid",
+                    segment
+                )))
+            );
+        }
+        assert!(claim_child(&Claim::new("read", ""), "-b").is_err());
+    }
+
+    #[test]
+    fn test_is_ancestor_of() {
+        let global = Claim::new("read", "");
+        let a = Claim::new("read", "a");
+        let a_b = Claim::new("read", "a.b");
+        let a_dash = Claim::new("read", "a-b");
+
+        assert!(claim_is_ancestor_of(&global, &a));
+        assert!(claim_is_ancestor_of(&global, &a_b));
+        assert!(claim_is_ancestor_of(&a, &a_b));
+        assert!(!claim_is_ancestor_of(&a, &a));
+        assert!(!claim_is_ancestor_of(&a, &a_dash));
+        assert!(!claim_is_ancestor_of(&a_b, &a));
+        assert!(!claim_is_ancestor_of(&Claim::new("admin", ""), &a));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::claim::{claim_check, claims_minimize, Claim};
+use std::fmt;
+
+/// A claim whose coverage was gained (or lost), except for the subjects
+/// under it in `except`, which were (or still are) covered already.
+#[derive(Debug, PartialEq)]
+pub struct CoverageChange {
+    pub claim: Claim,
+    pub except: Vec<Claim>,
+}
+
+/// The difference in coverage between two claim lists, with `claim_check`
+/// semantics, regardless of how each list is written.
+#[derive(Debug, PartialEq)]
+pub struct ClaimsDiff {
+    /// Minimal claims whose coverage the new list adds.
+    pub granted: Vec<CoverageChange>,
+    /// Minimal claims whose coverage the new list removes.
+    pub revoked: Vec<CoverageChange>,
+}
+
+impl ClaimsDiff {
+    /// Both lists cover exactly the same queries, i.e. the change is a pure
+    /// refactor.
+    pub fn is_equivalent(&self) -> bool {
+        self.granted.is_empty() && self.revoked.is_empty()
+    }
+}
+
+impl fmt::Display for ClaimsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_equivalent() {
+            return writeln!(f, "no change in coverage");
+        }
+
+        for (sign, changes) in [("+", &self.granted), ("-", &self.revoked)].iter() {
+            for change in changes.iter() {
+                write!(f, "{} {}", sign, change.claim)?;
+                if !change.except.is_empty() {
+                    let except: Vec<String> = change.except.iter().map(|c| c.to_string()).collect();
+                    write!(f, " (except {})", except.join(", "))?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn diff_claims<'a, 'b, I, J>(old: I, new: J) -> ClaimsDiff
+where
+    I: Iterator<Item = &'a Claim>,
+    J: Iterator<Item = &'b Claim>,
+{
+    let old = claims_minimize(old);
+    let new = claims_minimize(new);
+
+    ClaimsDiff {
+        granted: coverage_changes(&new, &old),
+        revoked: coverage_changes(&old, &new),
+    }
+}
+
+/// Claims of `from` not covered by `to`, each with the claims of `to` under it.
+fn coverage_changes(from: &[Claim], to: &[Claim]) -> Vec<CoverageChange> {
+    from.iter()
+        .filter(|c| !to.iter().any(|t| claim_check(t, c)))
+        .map(|c| CoverageChange {
+            claim: (*c).clone(),
+            except: to.iter().filter(|t| claim_check(c, t)).cloned().collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims;
+
+    fn diff(old: &[&str], new: &[&str]) -> ClaimsDiff {
+        let old = claims(old);
+        let new = claims(new);
+        diff_claims(old.iter(), new.iter())
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let result = diff(
+            &["read:a", "read:a.b", "write:*"],
+            &["write:x", "read:a", "write:*"],
+        );
+        assert!(result.is_equivalent());
+        assert_eq!(result.to_string(), "no change in coverage\n");
+    }
+
+    #[test]
+    fn test_widened() {
+        let result = diff(&["read:projects.a", "read:projects.b"], &["read:projects"]);
+        assert!(!result.is_equivalent());
+        assert!(result.revoked.is_empty());
+        assert_eq!(
+            result.granted,
+            vec![CoverageChange {
+                claim: Claim::new("read", "projects"),
+                except: vec![
+                    Claim::new("read", "projects.a"),
+                    Claim::new("read", "projects.b")
+                ],
+            }]
+        );
+        assert_eq!(
+            result.to_string(),
+            "+ read:projects (except read:projects.a, read:projects.b)\n"
+        );
+    }
+
+    #[test]
+    fn test_narrowed() {
+        let result = diff(&["read:*", "admin:users"], &["read:projects"]);
+        assert!(result.granted.is_empty());
+        assert_eq!(
+            result.to_string(),
+            "- admin:users\n- read:* (except read:projects)\n"
+        );
+    }
+
+    #[test]
+    fn test_both_ways() {
+        let result = diff(&["read:a", "write:a"], &["read:a-b", "write:a"]);
+        assert_eq!(result.to_string(), "+ read:a-b\n- read:a\n");
+    }
+}
@@ -0,0 +1,214 @@
+use crate::claim::{parse_query, Claim};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Principals and their claims, indexed by claim to answer "who can do X"
+/// without looking at every principal.
+#[derive(Debug, Default, PartialEq)]
+pub struct Directory {
+    principals: BTreeMap<String, Vec<Claim>>,
+    index: BTreeMap<Claim, BTreeSet<String>>,
+}
+
+impl Directory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.principals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.principals.is_empty()
+    }
+
+    /// Sets the claims of the principal, replacing the ones it had. They are
+    /// stored sorted and deduplicated.
+    pub fn insert<'a, I>(&mut self, principal: &str, claims: I)
+    where
+        I: Iterator<Item = &'a Claim>,
+    {
+        self.remove(principal);
+
+        let mut list: Vec<Claim> = claims.cloned().collect();
+        list.sort();
+        list.dedup();
+        for claim in list.iter() {
+            self.index
+                .entry(claim.clone())
+                .or_default()
+                .insert(String::from(principal));
+        }
+        self.principals.insert(String::from(principal), list);
+    }
+
+    /// Removes the principal, returning its claims.
+    pub fn remove(&mut self, principal: &str) -> Option<Vec<Claim>> {
+        let claims = self.principals.remove(principal)?;
+        for claim in claims.iter() {
+            if let Some(holders) = self.index.get_mut(claim) {
+                holders.remove(principal);
+                if holders.is_empty() {
+                    self.index.remove(claim);
+                }
+            }
+        }
+        Some(claims)
+    }
+
+    pub fn claims(&self, principal: &str) -> Option<&[Claim]> {
+        self.principals.get(principal).map(|c| c.as_slice())
+    }
+
+    /// The principals in alphabetical order.
+    pub fn principals(&self) -> impl Iterator<Item = &str> {
+        self.principals.keys().map(|p| p.as_str())
+    }
+
+    /// The principals with a claim covering the query, as `claim_check`
+    /// gives, in alphabetical order.
+    pub fn principals_check(&self, query: &Claim) -> Vec<&str> {
+        let mut found: BTreeSet<&str> = BTreeSet::new();
+        for claim in std::iter::once(query.clone()).chain(query.ancestors()) {
+            if let Some(holders) = self.index.get(&claim) {
+                found.extend(holders.iter().map(|p| p.as_str()));
+            }
+        }
+        found.into_iter().collect()
+    }
+
+    /// Like `principals_check`, returning no principals for an invalid query.
+    pub fn principals_check_str(&self, query: &str) -> Vec<&str> {
+        let parse_result = parse_query(query);
+        if let Ok(parsed) = parse_result {
+            self.principals_check(&parsed)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// The principals holding exactly the claim, as `claim_exact` gives, in
+    /// alphabetical order.
+    pub fn principals_exact(&self, claim: &Claim) -> Vec<&str> {
+        match self.index.get(claim) {
+            Some(holders) => holders.iter().map(|p| p.as_str()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Like `principals_exact`, returning no principals for an invalid claim.
+    pub fn principals_exact_str(&self, claim: &str) -> Vec<&str> {
+        let parse_result = parse_query(claim);
+        if let Ok(parsed) = parse_result {
+            self.principals_exact(&parsed)
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claim::{claim_check, claim_exact};
+    use crate::test_util::claims;
+
+    fn directory() -> Directory {
+        let mut dir = Directory::new();
+        let entries: [(&str, &[&str]); 4] = [
+            ("alice", &["delete:billing", "read:*"]),
+            ("bob", &["delete:billing.invoices", "read:projects"]),
+            ("carol", &["delete:*"]),
+            ("dave", &["delete:billing-old", "read:projects.1"]),
+        ];
+        for (principal, list) in entries.iter() {
+            dir.insert(principal, claims(list).iter());
+        }
+        dir
+    }
+
+    #[test]
+    fn test_principals_check() {
+        let dir = directory();
+        assert_eq!(
+            dir.principals_check_str("delete:billing.*"),
+            vec!["alice", "carol"]
+        );
+        assert_eq!(
+            dir.principals_check_str("delete:billing.invoices.3"),
+            vec!["alice", "bob", "carol"]
+        );
+        assert_eq!(dir.principals_check_str("delete:*"), vec!["carol"]);
+        assert_eq!(
+            dir.principals_check_str("read:projects.1.x"),
+            vec!["alice", "bob", "dave"]
+        );
+        assert!(dir.principals_check_str("write:anything").is_empty());
+        assert!(dir.principals_check_str("not valid").is_empty());
+    }
+
+    #[test]
+    fn test_principals_exact() {
+        let dir = directory();
+        assert_eq!(dir.principals_exact_str("delete:billing"), vec!["alice"]);
+        assert_eq!(dir.principals_exact_str("read:projects.*"), vec!["bob"]);
+        assert!(dir.principals_exact_str("read:projects.1.x").is_empty());
+        assert!(dir.principals_exact_str("not valid").is_empty());
+    }
+
+    #[test]
+    fn test_consistent_with_claim_check() {
+        let dir = directory();
+        let queries = claims(&[
+            "delete:*",
+            "delete:billing",
+            "delete:billing-old.x",
+            "delete:billing.invoices",
+            "read:*",
+            "read:projects",
+            "read:projects.1",
+            "read:users",
+        ]);
+
+        for query in queries.iter() {
+            let check: Vec<&str> = dir
+                .principals()
+                .filter(|p| dir.claims(p).unwrap().iter().any(|c| claim_check(c, query)))
+                .collect();
+            assert_eq!(dir.principals_check(query), check, "query {}", query);
+
+            let exact: Vec<&str> = dir
+                .principals()
+                .filter(|p| dir.claims(p).unwrap().iter().any(|c| claim_exact(c, query)))
+                .collect();
+            assert_eq!(dir.principals_exact(query), exact, "query {}", query);
+        }
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut dir = directory();
+        assert_eq!(dir.len(), 4);
+
+        dir.insert("alice", claims(&["read:users", "read:users"]).iter());
+        assert_eq!(dir.len(), 4);
+        assert_eq!(
+            dir.claims("alice"),
+            Some(&[Claim::new("read", "users")][..])
+        );
+        assert_eq!(dir.principals_check_str("delete:billing"), vec!["carol"]);
+        assert_eq!(dir.principals_exact_str("read:users"), vec!["alice"]);
+
+        let removed = dir.remove("carol").unwrap();
+        assert_eq!(removed, vec![Claim::new("delete", "")]);
+        assert!(dir.remove("carol").is_none());
+        assert!(dir.principals_check_str("delete:billing").is_empty());
+        assert_eq!(dir.claims("carol"), None);
+
+        for principal in ["alice", "bob", "dave"].iter() {
+            dir.remove(principal);
+        }
+        assert!(dir.is_empty());
+        assert_eq!(dir, Directory::new());
+    }
+}
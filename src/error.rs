@@ -8,6 +8,9 @@ pub enum Error {
     /// A syntax error.
     Syntax(String),
 
+    /// An I/O error while reading input, with its description.
+    Io(String),
+
     /// Hints that destructuring should not be exhaustive.
     ///
     /// This enum may grow additional variants, so this makes sure clients
@@ -19,10 +22,17 @@ pub enum Error {
 
 impl ::std::error::Error for Error {}
 
+impl From<::std::io::Error> for Error {
+    fn from(err: ::std::io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::Syntax(ref err) => err.fmt(f),
+            Error::Io(ref err) => err.fmt(f),
             Error::__NonExhaustive => unreachable!(),
         }
     }
@@ -36,5 +46,8 @@ mod tests {
     fn test_fmt() {
         let e = Error::Syntax("something".to_string());
         assert_eq!(format!("{}", e), "something");
+
+        let e = Error::Io("broken pipe".to_string());
+        assert_eq!(format!("{}", e), "broken pipe");
     }
 }
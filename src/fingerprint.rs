@@ -0,0 +1,87 @@
+use crate::claim::{claims_minimize, Claim};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// A stable fingerprint of the queries a claim list covers, e.g. for cache
+/// keys and ETags. Lists that cover the same queries get the same
+/// fingerprint regardless of order, duplicates or redundant claims.
+///
+/// It is the SHA-256 of the minimized claims, one per line after a version
+/// line, so it does not change across processes, platforms or versions of
+/// this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(pub [u8; 32]);
+
+/// Displayed as 64 lowercase hex digits.
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.0.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn claims_fingerprint<'a, I>(claims: I) -> Fingerprint
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let mut hasher = Sha256::new();
+    hasher.update(b"claims-fingerprint-v1\n");
+    for claim in claims_minimize(claims) {
+        hasher.update(claim.to_string().as_bytes());
+        hasher.update(b"\n");
+    }
+    Fingerprint(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims_in_order;
+
+    fn fingerprint(list: &[&str]) -> Fingerprint {
+        claims_fingerprint(claims_in_order(list).iter())
+    }
+
+    #[test]
+    fn test_equivalent_lists() {
+        let expected = fingerprint(&["read:a", "write:*"]);
+        assert_eq!(fingerprint(&["write:*", "read:a"]), expected);
+        assert_eq!(fingerprint(&["read:a", "read:a", "write:*"]), expected);
+        assert_eq!(fingerprint(&["read:a.*", "write:*"]), expected);
+        assert_eq!(fingerprint(&["read:a.", "write:x", "write:*"]), expected);
+        assert_eq!(fingerprint(&["read:a.b", "read:a", "write:*"]), expected);
+    }
+
+    #[test]
+    fn test_different_lists() {
+        let fingerprints = [
+            fingerprint(&[]),
+            fingerprint(&["read:a"]),
+            fingerprint(&["read:a.b"]),
+            fingerprint(&["read:*"]),
+            fingerprint(&["read:a", "read:b"]),
+            fingerprint(&["read:a-b"]),
+            fingerprint(&["write:a"]),
+        ];
+        for (idx, a) in fingerprints.iter().enumerate() {
+            for b in fingerprints[idx + 1..].iter() {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stable_value() {
+        // must never change: fingerprints are stored by callers
+        assert_eq!(
+            fingerprint(&[]).to_string(),
+            "cf58e09a6c102cdedab8080aae986819f168ffbda1864699c468ad2c7fd02d60"
+        );
+        assert_eq!(
+            fingerprint(&["write:*", "read:a"]).to_string(),
+            "93fdb93bf59676a3c9ee82001b5a1a83024d5f62aac189cf80305386df5ad039"
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use crate::claim::{claim_child, claim_from_parts, Claim};
+use crate::error::Error;
+use std::collections::HashMap;
+
+/// Maps an HTTP method and path to a claim, e.g. `GET /projects/42/settings`
+/// to `read:projects.42.settings`.
+///
+/// Path segments are percent-decoded and must then be valid subject
+/// segments; they are never rewritten, so two different paths can't map to
+/// the same claim.
+#[derive(Debug, Clone)]
+pub struct HttpClaimMapper {
+    verbs: HashMap<String, String>,
+    routes: Vec<Route>,
+}
+
+impl HttpClaimMapper {
+    /// Mapper with the default verbs: `GET` to `read`, `POST`, `PUT` and
+    /// `PATCH` to `write` and `DELETE` to `delete`.
+    pub fn new() -> Self {
+        let mut verbs = HashMap::new();
+        for (method, verb) in [
+            ("GET", "read"),
+            ("POST", "write"),
+            ("PUT", "write"),
+            ("PATCH", "write"),
+            ("DELETE", "delete"),
+        ]
+        .iter()
+        {
+            verbs.insert(method.to_string(), verb.to_string());
+        }
+
+        Self {
+            verbs,
+            routes: Vec::new(),
+        }
+    }
+
+    /// Fails if the verb is not valid in a claim.
+    pub fn with_verb(mut self, method: &str, verb: &str) -> Result<Self, Error> {
+        if claim_from_parts(verb, "").is_err() {
+            return Err(Error::Syntax(format!(
+                "the given verb {} is not valid",
+                verb
+            )));
+        }
+        self.verbs
+            .insert(method.to_ascii_uppercase(), String::from(verb));
+        Ok(self)
+    }
+
+    pub fn without_verb(mut self, method: &str) -> Self {
+        self.verbs.remove(&method.to_ascii_uppercase());
+        self
+    }
+
+    /// Routes are tried in the order they were added; paths not matching any
+    /// route are mapped segment by segment.
+    pub fn with_route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    pub fn verb_for(&self, method: &str) -> Option<&str> {
+        self.verbs
+            .get(&method.to_ascii_uppercase())
+            .map(|v| v.as_str())
+    }
+
+    /// Returns `None` if the method has no verb mapped, or if a segment is
+    /// not correctly percent-encoded or is not a valid subject segment, e.g.
+    /// `/files/a.b` or `/files/a%20b`.
+    pub fn map(&self, method: &str, path: &str) -> Option<Claim> {
+        let verb = self.verb_for(method)?;
+        let segments = path_segments(path)
+            .iter()
+            .map(|s| percent_decode(s))
+            .collect::<Option<Vec<String>>>()?;
+
+        let subject_segments = self
+            .routes
+            .iter()
+            .find_map(|r| r.apply(&segments))
+            .unwrap_or(segments);
+
+        let mut claim = Claim::new(verb, "");
+        for segment in subject_segments.iter() {
+            claim = claim_child(&claim, segment).ok()?;
+        }
+        Some(claim)
+    }
+}
+
+impl Default for HttpClaimMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PatternSegment {
+    Literal(String),
+    Capture(String),
+    Rest(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplateSegment {
+    Literal(String),
+    Capture(String),
+}
+
+/// A route pattern such as `/api/v1/projects/:id/*rest` together with the
+/// subject template it maps to, such as `projects.:id.*rest`.
+///
+/// `:name` captures a single path segment and `*name` (only allowed last)
+/// captures all remaining ones. Path segments not referenced by the template
+/// are dropped, and literal template segments rename them. Literal pattern
+/// segments are compared with the decoded path, like captures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pattern: Vec<PatternSegment>,
+    template: Vec<TemplateSegment>,
+}
+
+impl Route {
+    /// Fails if a literal template segment is not a valid subject segment.
+    pub fn new(pattern: &str, template: &str) -> Result<Self, Error> {
+        let parts = path_segments(pattern);
+        let mut parsed_pattern = Vec::with_capacity(parts.len());
+        for (idx, part) in parts.iter().enumerate() {
+            let segment = if let Some(name) = part.strip_prefix(':') {
+                PatternSegment::Capture(String::from(name))
+            } else if let Some(name) = part.strip_prefix('*') {
+                if idx != parts.len() - 1 {
+                    return Err(err_invalid_route(pattern));
+                }
+                PatternSegment::Rest(String::from(name))
+            } else {
+                let literal = percent_decode(part).ok_or_else(|| err_invalid_route(pattern))?;
+                PatternSegment::Literal(literal)
+            };
+            parsed_pattern.push(segment);
+        }
+
+        let mut parsed_template = Vec::new();
+        for part in template.split('.').filter(|p| !p.is_empty() && *p != "*") {
+            let segment = match part.strip_prefix(':').or_else(|| part.strip_prefix('*')) {
+                Some(name) => {
+                    let known = parsed_pattern.iter().any(|p| match p {
+                        PatternSegment::Capture(n) | PatternSegment::Rest(n) => n == name,
+                        PatternSegment::Literal(_) => false,
+                    });
+                    if !known {
+                        return Err(err_invalid_route(pattern));
+                    }
+                    TemplateSegment::Capture(String::from(name))
+                }
+                None => {
+                    if claim_child(&Claim::new("any", ""), part).is_err() {
+                        return Err(err_invalid_route(pattern));
+                    }
+                    TemplateSegment::Literal(String::from(part))
+                }
+            };
+            parsed_template.push(segment);
+        }
+
+        Ok(Self {
+            pattern: parsed_pattern,
+            template: parsed_template,
+        })
+    }
+
+    fn apply(&self, segments: &[String]) -> Option<Vec<String>> {
+        let mut captures: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut idx = 0;
+        for part in self.pattern.iter() {
+            match part {
+                PatternSegment::Literal(lit) => {
+                    if segments.get(idx) != Some(lit) {
+                        return None;
+                    }
+                    idx += 1;
+                }
+                PatternSegment::Capture(name) => {
+                    let segment = segments.get(idx)?;
+                    captures.insert(name, vec![segment.clone()]);
+                    idx += 1;
+                }
+                PatternSegment::Rest(name) => {
+                    captures.insert(name, segments[idx..].to_vec());
+                    idx = segments.len();
+                }
+            }
+        }
+
+        if idx != segments.len() {
+            return None;
+        }
+
+        let mut subject = Vec::new();
+        for part in self.template.iter() {
+            match part {
+                TemplateSegment::Literal(lit) => subject.push(lit.clone()),
+                TemplateSegment::Capture(name) => {
+                    subject.extend(captures.get(name.as_str()).cloned().unwrap_or_default())
+                }
+            }
+        }
+        Some(subject)
+    }
+}
+
+fn path_segments(path: &str) -> Vec<&str> {
+    let end = path.find(['?', '#']).unwrap_or(path.len());
+    path[..end].split('/').filter(|s| !s.is_empty()).collect()
+}
+
+/// Decodes `%XX` escapes, `None` if one is incomplete or the result is not
+/// UTF-8.
+fn percent_decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            let hex = bytes.get(idx + 1..idx + 3)?;
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            idx += 3;
+        } else {
+            decoded.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+fn err_invalid_route(pattern: &str) -> Error {
+    Error::Syntax(format!("the given route {} is not valid", pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claim::is_valid_claim_str;
+
+    fn map(mapper: &HttpClaimMapper, method: &str, path: &str) -> Option<String> {
+        mapper.map(method, path).map(|c| c.to_string())
+    }
+
+    #[test]
+    fn test_default_verbs() {
+        let mapper = HttpClaimMapper::new();
+        let path = "/projects/42/settings";
+        let expected = |s: &str| Some(format!("{}:projects.42.settings", s));
+
+        assert_eq!(map(&mapper, "GET", path), expected("read"));
+        assert_eq!(map(&mapper, "get", path), expected("read"));
+        assert_eq!(map(&mapper, "POST", path), expected("write"));
+        assert_eq!(map(&mapper, "PUT", path), expected("write"));
+        assert_eq!(map(&mapper, "PATCH", path), expected("write"));
+        assert_eq!(map(&mapper, "DELETE", path), expected("delete"));
+        assert_eq!(map(&mapper, "OPTIONS", path), None);
+    }
+
+    #[test]
+    fn test_overridden_verbs() {
+        let mapper = HttpClaimMapper::new()
+            .with_verb("head", "read")
+            .unwrap()
+            .with_verb("POST", "create")
+            .unwrap()
+            .without_verb("DELETE");
+
+        assert_eq!(map(&mapper, "HEAD", "/a"), Some("read:a".to_string()));
+        assert_eq!(map(&mapper, "POST", "/a"), Some("create:a".to_string()));
+        assert_eq!(map(&mapper, "DELETE", "/a"), None);
+    }
+
+    #[test]
+    fn test_invalid_verbs() {
+        for verb in ["bad verb", "", "read:x", "*"].iter() {
+            assert_eq!(
+                HttpClaimMapper::new().with_verb("HEAD", verb).unwrap_err(),
+                Error::Syntax(format!("the given verb {} is not valid", verb))
+            );
+        }
+    }
+
+    #[test]
+    fn test_root_is_global() {
+        let mapper = HttpClaimMapper::new();
+        assert_eq!(map(&mapper, "GET", "/"), Some("read:*".to_string()));
+        assert_eq!(map(&mapper, "GET", ""), Some("read:*".to_string()));
+    }
+
+    #[test]
+    fn test_ignores_query_and_empty_segments() {
+        let mapper = HttpClaimMapper::new();
+        assert_eq!(
+            map(&mapper, "GET", "//projects//42/?page=2#top"),
+            Some("read:projects.42".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decodes_segments() {
+        let mapper = HttpClaimMapper::new();
+        let paths = [
+            ("/projects/4%32", "read:projects.42"),
+            ("/a/b-c", "read:a.b-c"),
+            ("/a/%C3%B1", "read:a.ñ"),
+            ("/ñandú/é", "read:ñandú.é"),
+        ];
+        for (path, expected) in paths.iter() {
+            let claim = map(&mapper, "GET", path).unwrap();
+            assert_eq!(&claim, expected);
+            assert!(is_valid_claim_str(&claim), "{}", claim);
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_segments() {
+        let mapper = HttpClaimMapper::new();
+        let paths = [
+            "/files/report.pdf",
+            "/files/a%2Eb",
+            "/files/a b",
+            "/files/a%20b",
+            "/-x",
+            "/a/²",
+            "/a/%2",
+            "/a/%zz",
+            "/a/%+1",
+            "/a/%ff",
+        ];
+        for path in paths.iter() {
+            assert_eq!(map(&mapper, "GET", path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_no_collisions() {
+        let mapper = HttpClaimMapper::new();
+        let claims: Vec<Option<String>> = ["/files/a.b", "/files/a b", "/files/a_b"]
+            .iter()
+            .map(|path| map(&mapper, "GET", path))
+            .collect();
+        assert_eq!(claims, vec![None, None, Some("read:files.a_b".to_string())]);
+    }
+
+    #[test]
+    fn test_route_drops_and_renames() {
+        let mapper = HttpClaimMapper::new()
+            .with_route(Route::new("/api/v1/projects/:id/settings", "projects.:id.config").unwrap())
+            .with_route(Route::new("/api/v1/orgs/:org/*rest", "organisations.:org.*rest").unwrap());
+
+        assert_eq!(
+            map(&mapper, "GET", "/api/v1/projects/42/settings"),
+            Some("read:projects.42.config".to_string())
+        );
+        assert_eq!(
+            map(&mapper, "GET", "/api/v1/orgs/acme/teams/a_b"),
+            Some("read:organisations.acme.teams.a_b".to_string())
+        );
+        assert_eq!(map(&mapper, "GET", "/api/v1/orgs/acme/teams/a.b"), None);
+        assert_eq!(
+            map(&mapper, "GET", "/api/v1/orgs/acme"),
+            Some("read:organisations.acme".to_string())
+        );
+        assert_eq!(
+            map(&mapper, "GET", "/api/v1/projects/42"),
+            Some("read:api.v1.projects.42".to_string())
+        );
+    }
+
+    #[test]
+    fn test_route_to_global() {
+        let mapper = HttpClaimMapper::new().with_route(Route::new("/health", "*").unwrap());
+        assert_eq!(map(&mapper, "GET", "/health"), Some("read:*".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_routes() {
+        assert_eq!(
+            Route::new("/a/*rest/b", "a"),
+            Err(Error::Syntax(
+                "the given route /a/*rest/b is not valid".to_string()
+            ))
+        );
+        assert!(Route::new("/a/:id", "a.:other").is_err());
+        assert!(Route::new("/a/:id", "a b.:id").is_err());
+        assert!(Route::new("/a%zz/:id", "a.:id").is_err());
+    }
+}
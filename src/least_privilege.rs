@@ -0,0 +1,220 @@
+use crate::claim::{claim_check, claims_direct_descendants, claims_minimize, Claim};
+use std::collections::{BTreeMap, HashMap};
+
+/// The minimal claims a principal needs given its observed usage, and how
+/// they differ from its current claims.
+#[derive(Debug, PartialEq)]
+pub struct Recommendation {
+    pub principal: String,
+    /// Minimized and sorted.
+    pub recommended: Vec<Claim>,
+    /// Recommended claims the principal does not hold today.
+    pub added: Vec<Claim>,
+    /// Current claims that are not recommended.
+    pub removed: Vec<Claim>,
+}
+
+/// Computes, per principal, the minimal claims that would still have allowed
+/// every observed query.
+///
+/// With `generalize_at: Some(n)`, when the recommended claims under a subject
+/// have at least `n` distinct direct descendants (as given by
+/// `claims_direct_descendants`) they are replaced by that subject, as long as
+/// the principal's current claims already cover it. Principals with current
+/// claims but no usage get an empty recommendation.
+pub fn recommend<'a, I>(
+    usage: I,
+    current: &HashMap<String, Vec<Claim>>,
+    generalize_at: Option<usize>,
+) -> Vec<Recommendation>
+where
+    I: Iterator<Item = (&'a str, &'a Claim)>,
+{
+    let mut observed: BTreeMap<&str, Vec<&Claim>> = BTreeMap::new();
+    for principal in current.keys() {
+        observed.entry(principal.as_str()).or_default();
+    }
+    for (principal, query) in usage {
+        observed.entry(principal).or_default().push(query);
+    }
+
+    let no_claims = Vec::new();
+    observed
+        .into_iter()
+        .map(|(principal, queries)| {
+            let held = current.get(principal).unwrap_or(&no_claims);
+
+            let mut recommended = claims_minimize(queries.into_iter());
+            if let Some(threshold) = generalize_at {
+                recommended = generalize(recommended, held, threshold.max(1));
+            }
+
+            let added = recommended
+                .iter()
+                .filter(|c| !held.contains(c))
+                .cloned()
+                .collect();
+            let mut removed: Vec<Claim> = held
+                .iter()
+                .filter(|c| !recommended.contains(c))
+                .cloned()
+                .collect();
+            removed.sort();
+            removed.dedup();
+
+            Recommendation {
+                principal: String::from(principal),
+                recommended,
+                added,
+                removed,
+            }
+        })
+        .collect()
+}
+
+fn generalize(mut claims: Vec<Claim>, held: &[Claim], threshold: usize) -> Vec<Claim> {
+    loop {
+        let mut parents: Vec<Claim> = claims.iter().filter_map(|c| c.parent()).collect();
+        // deepest first, so that generalizing can cascade upwards
+        parents.sort_by_key(|p| (std::cmp::Reverse(p.depth()), p.to_string()));
+        parents.dedup();
+
+        let candidate = parents.into_iter().find(|p| {
+            held.iter().any(|h| claim_check(h, p))
+                && claims_direct_descendants(claims.iter(), p).len() >= threshold
+        });
+
+        match candidate {
+            Some(parent) => {
+                claims.push(parent);
+                claims = claims_minimize(claims.iter());
+            }
+            None => return claims,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{claims, strings};
+
+    fn current(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<Claim>> {
+        entries
+            .iter()
+            .map(|(p, c)| (p.to_string(), claims(c)))
+            .collect()
+    }
+
+    #[test]
+    fn test_minimal_set() {
+        let queries = claims(&[
+            "read:projects.1",
+            "read:projects.1.settings",
+            "write:projects.2",
+        ]);
+        let usage = queries.iter().map(|q| ("alice", q));
+        let held = current(&[("alice", &["write:*", "read:*"])]);
+
+        let result = recommend(usage, &held, None);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].principal, "alice");
+        assert_eq!(
+            strings(&result[0].recommended),
+            vec!["read:projects.1", "write:projects.2"]
+        );
+        assert_eq!(
+            strings(&result[0].added),
+            vec!["read:projects.1", "write:projects.2"]
+        );
+        assert_eq!(strings(&result[0].removed), vec!["read:*", "write:*"]);
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let queries = claims(&["read:projects.1"]);
+        let usage = queries.iter().map(|q| ("alice", q));
+        let held = current(&[("alice", &["read:projects.1"])]);
+
+        let result = recommend(usage, &held, Some(2));
+        assert_eq!(strings(&result[0].recommended), vec!["read:projects.1"]);
+        assert!(result[0].added.is_empty());
+        assert!(result[0].removed.is_empty());
+    }
+
+    #[test]
+    fn test_unused_principal() {
+        let held = current(&[("bob", &["admin:*"])]);
+        let result = recommend(std::iter::empty(), &held, Some(2));
+
+        assert_eq!(result.len(), 1);
+        assert!(result[0].recommended.is_empty());
+        assert_eq!(strings(&result[0].removed), vec!["admin:*"]);
+    }
+
+    #[test]
+    fn test_generalize() {
+        let queries = claims(&[
+            "read:projects.1.a",
+            "read:projects.2",
+            "read:projects.3.b",
+            "read:users.1",
+        ]);
+        let usage = queries.iter().map(|q| ("alice", q));
+        let held = current(&[("alice", &["read:*"])]);
+
+        let result = recommend(usage.clone(), &held, Some(3));
+        assert_eq!(
+            strings(&result[0].recommended),
+            vec!["read:projects", "read:users.1"]
+        );
+
+        let result = recommend(usage.clone(), &held, Some(2));
+        assert_eq!(strings(&result[0].recommended), vec!["read:*"]);
+
+        let result = recommend(usage, &held, Some(4));
+        assert_eq!(
+            strings(&result[0].recommended),
+            vec![
+                "read:projects.1.a",
+                "read:projects.2",
+                "read:projects.3.b",
+                "read:users.1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generalize_never_widens() {
+        let queries = claims(&["read:projects.1", "read:projects.2", "read:projects.3"]);
+        let usage = queries.iter().map(|q| ("alice", q));
+        let held = current(&[(
+            "alice",
+            &["read:projects.1", "read:projects.2", "read:projects.3"],
+        )]);
+
+        let result = recommend(usage, &held, Some(2));
+        assert_eq!(
+            strings(&result[0].recommended),
+            vec!["read:projects.1", "read:projects.2", "read:projects.3"]
+        );
+        assert!(result[0].removed.is_empty());
+    }
+
+    #[test]
+    fn test_several_principals() {
+        let alice = claims(&["read:a"]);
+        let bob = claims(&["read:b", "read:b.c"]);
+        let usage = alice
+            .iter()
+            .map(|q| ("alice", q))
+            .chain(bob.iter().map(|q| ("bob", q)));
+        let held = current(&[("alice", &["read:*"]), ("bob", &["read:b"])]);
+
+        let result = recommend(usage, &held, None);
+        let principals: Vec<&str> = result.iter().map(|r| r.principal.as_str()).collect();
+        assert_eq!(principals, vec!["alice", "bob"]);
+        assert_eq!(strings(&result[1].recommended), vec!["read:b"]);
+        assert!(result[1].removed.is_empty());
+    }
+}
@@ -1,5 +1,26 @@
 extern crate lazy_static;
 extern crate regex;
 
+pub mod audit;
 pub mod claim;
+pub mod diff;
+pub mod directory;
+pub mod encoding;
 pub mod error;
+#[cfg(feature = "fingerprint")]
+pub mod fingerprint;
+pub mod http;
+pub mod least_privilege;
+pub mod lint;
+#[cfg(feature = "redact")]
+pub mod redact;
+#[cfg(feature = "json")]
+pub mod replay;
+pub mod resource;
+pub mod scope;
+pub mod sql;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "token")]
+pub mod token;
+pub mod tree;
@@ -0,0 +1,239 @@
+use crate::claim::{claim_check, Claim};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// A claim is covered by another claim of the same list (or repeated).
+    /// The offending claims are the subsumed one and the one covering it.
+    Subsumed,
+    /// A prohibition covers nothing that is permitted and nothing permitted
+    /// covers it, so it never changes a decision.
+    UselessProhibition,
+    /// A permitted claim is fully covered by a prohibition. The offending
+    /// claims are the permitted one and the prohibition.
+    CancelledPermission,
+    /// A verb appears in a single claim across both lists, likely a typo.
+    RareVerb,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub severity: Severity,
+    pub claims: Vec<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Lints a policy made of permitted and prohibited claims, where a query is
+/// allowed if a permitted claim covers it and no prohibited claim does.
+pub fn lint_policy(permitted: &[Claim], prohibited: &[Claim]) -> Vec<Lint> {
+    let mut lints = Vec::new();
+
+    lint_subsumed(permitted, "permitted", &mut lints);
+    lint_subsumed(prohibited, "prohibited", &mut lints);
+
+    for claim in permitted.iter() {
+        if let Some(prohibition) = prohibited.iter().find(|p| claim_check(p, claim)) {
+            lints.push(Lint {
+                kind: LintKind::CancelledPermission,
+                severity: Severity::Error,
+                claims: vec![claim.to_string(), prohibition.to_string()],
+                message: format!(
+                    "permitted claim {} is cancelled by prohibited claim {}",
+                    claim, prohibition
+                ),
+            });
+        }
+    }
+
+    for prohibition in prohibited.iter() {
+        let overlaps = permitted
+            .iter()
+            .any(|c| claim_check(c, prohibition) || claim_check(prohibition, c));
+        if !overlaps {
+            lints.push(Lint {
+                kind: LintKind::UselessProhibition,
+                severity: Severity::Warning,
+                claims: vec![prohibition.to_string()],
+                message: format!(
+                    "prohibited claim {} does not overlap any permitted claim",
+                    prohibition
+                ),
+            });
+        }
+    }
+
+    let mut verbs: BTreeMap<&str, Vec<&Claim>> = BTreeMap::new();
+    for claim in permitted.iter().chain(prohibited.iter()) {
+        verbs.entry(&claim.verb).or_default().push(claim);
+    }
+    for (verb, claims) in verbs.iter().filter(|(_, c)| c.len() == 1) {
+        lints.push(Lint {
+            kind: LintKind::RareVerb,
+            severity: Severity::Info,
+            claims: vec![claims[0].to_string()],
+            message: format!("verb {} is only used by {}", verb, claims[0]),
+        });
+    }
+
+    lints
+}
+
+fn lint_subsumed(claims: &[Claim], list_name: &str, lints: &mut Vec<Lint>) {
+    for (idx, claim) in claims.iter().enumerate() {
+        let covering = claims
+            .iter()
+            .enumerate()
+            .find(|(other_idx, other)| {
+                *other_idx != idx
+                    && claim_check(other, claim)
+                    && (*other != claim || *other_idx < idx)
+            })
+            .map(|(_, other)| other);
+
+        if let Some(other) = covering {
+            let message = if other == claim {
+                format!("{} claim {} is repeated", list_name, claim)
+            } else {
+                format!("{} claim {} is covered by {}", list_name, claim, other)
+            };
+            lints.push(Lint {
+                kind: LintKind::Subsumed,
+                severity: Severity::Warning,
+                claims: vec![claim.to_string(), other.to_string()],
+                message,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims_in_order;
+
+    fn kinds(lints: &[Lint], kind: LintKind) -> Vec<Vec<String>> {
+        lints
+            .iter()
+            .filter(|l| l.kind == kind)
+            .map(|l| l.claims.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_policy() {
+        let permitted = claims_in_order(&["read:projects", "read:users", "write:projects"]);
+        let prohibited = claims_in_order(&["read:projects.secret", "write:projects.secret"]);
+        assert_eq!(lint_policy(&permitted, &prohibited), Vec::new());
+    }
+
+    #[test]
+    fn test_subsumed() {
+        let permitted = claims_in_order(&[
+            "read:a.b", "read:a", "read:a", "read:a-b", "write:a", "write:b",
+        ]);
+        let prohibited = claims_in_order(&["read:a.b.c", "read:a.b.c.d"]);
+        let lints = lint_policy(&permitted, &prohibited);
+
+        assert_eq!(
+            kinds(&lints, LintKind::Subsumed),
+            vec![
+                vec!["read:a.b".to_string(), "read:a".to_string()],
+                vec!["read:a".to_string(), "read:a".to_string()],
+                vec!["read:a.b.c.d".to_string(), "read:a.b.c".to_string()],
+            ]
+        );
+        let messages: Vec<String> = lints
+            .iter()
+            .filter(|l| l.kind == LintKind::Subsumed)
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "warning: permitted claim read:a.b is covered by read:a",
+                "warning: permitted claim read:a is repeated",
+                "warning: prohibited claim read:a.b.c.d is covered by read:a.b.c",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_useless_prohibition() {
+        let permitted = claims_in_order(&["read:projects", "write:projects.1", "write:users"]);
+        let prohibited = claims_in_order(&["read:users", "write:projects", "read:projects.1"]);
+        let lints = lint_policy(&permitted, &prohibited);
+
+        assert_eq!(
+            kinds(&lints, LintKind::UselessProhibition),
+            vec![vec!["read:users".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_cancelled_permission() {
+        let permitted = claims_in_order(&["read:projects.1", "read:users", "write:projects.2"]);
+        let prohibited = claims_in_order(&["read:*", "write:projects"]);
+        let lints = lint_policy(&permitted, &prohibited);
+
+        let cancelled: Vec<&Lint> = lints
+            .iter()
+            .filter(|l| l.kind == LintKind::CancelledPermission)
+            .collect();
+        assert_eq!(cancelled.len(), 3);
+        assert!(cancelled.iter().all(|l| l.severity == Severity::Error));
+        assert_eq!(
+            cancelled[2].to_string(),
+            "error: permitted claim write:projects.2 is cancelled by prohibited claim write:projects"
+        );
+    }
+
+    #[test]
+    fn test_rare_verb() {
+        let permitted = claims_in_order(&["read:a", "read:b", "raed:c", "write:a"]);
+        let prohibited = claims_in_order(&["write:a.b"]);
+        let lints = lint_policy(&permitted, &prohibited);
+
+        let rare: Vec<&Lint> = lints
+            .iter()
+            .filter(|l| l.kind == LintKind::RareVerb)
+            .collect();
+        assert_eq!(rare.len(), 1);
+        assert_eq!(rare[0].claims, vec!["raed:c".to_string()]);
+        assert_eq!(rare[0].severity, Severity::Info);
+        assert_eq!(
+            rare[0].to_string(),
+            "info: verb raed is only used by raed:c"
+        );
+    }
+
+    #[test]
+    fn test_severity_order() {
+        assert!(Severity::Info < Severity::Warning);
+        assert!(Severity::Warning < Severity::Error);
+    }
+}
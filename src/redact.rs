@@ -0,0 +1,345 @@
+use crate::claim::{claim_check_parts, claim_child, claim_from_parts, Claim};
+use crate::error::Error;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// What to do with a field the claims do not cover.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedactMode {
+    Remove,
+    /// Replace the value with the given one, e.g. `"***"`.
+    Mask(Value),
+}
+
+/// Hides the fields of JSON values that the caller's claims do not cover.
+///
+/// Each field has the dotted path of its keys under the configured subject
+/// prefix, e.g. with prefix `users` the field `{"address": {"city": ..}}`
+/// has the subject `users.address.city`. Array elements share the path of
+/// the array. A field is kept whole if a claim covers its subject per
+/// `claim_check`, walked into if a claim covers something under it, and
+/// redacted otherwise. Keys that are not a valid subject segment, such as
+/// `profile.ssn`, are never covered, unless a claim covers the object holding
+/// them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redactor {
+    root: Claim,
+    mode: RedactMode,
+}
+
+impl Redactor {
+    /// Removes the fields that are not covered, see `with_mask`.
+    pub fn new(verb: &str, prefix: &str) -> Result<Self, Error> {
+        Ok(Redactor {
+            root: claim_from_parts(verb, prefix)?,
+            mode: RedactMode::Remove,
+        })
+    }
+
+    pub fn with_mask(mut self, mask: Value) -> Self {
+        self.mode = RedactMode::Mask(mask);
+        self
+    }
+
+    pub fn mode(&self) -> &RedactMode {
+        &self.mode
+    }
+
+    /// Redacts the value in place. A root object or array is always walked,
+    /// even if nothing under it is covered; any other root value that is not
+    /// covered is masked, or replaced with `null` when removing.
+    pub fn redact_value(&self, claims: &[Claim], value: &mut Value) {
+        if self.covers(claims, &self.root.subject) {
+            return;
+        }
+        match value {
+            Value::Object(_) | Value::Array(_) => {
+                self.redact_children(claims, &self.root.subject, value)
+            }
+            _ => {
+                if !self.deny(value) {
+                    *value = Value::Null;
+                }
+            }
+        }
+    }
+
+    /// Serializes the value to JSON and redacts it.
+    pub fn redact<T: Serialize + ?Sized>(
+        &self,
+        claims: &[Claim],
+        value: &T,
+    ) -> Result<Value, Error> {
+        let mut json = serde_json::to_value(value)
+            .map_err(|e| Error::Syntax(format!("the given value cannot be serialized: {}", e)))?;
+        self.redact_value(claims, &mut json);
+        Ok(json)
+    }
+
+    fn covers(&self, claims: &[Claim], subject: &str) -> bool {
+        claims
+            .iter()
+            .any(|c| claim_check_parts(c, &self.root.verb, subject))
+    }
+
+    fn covers_under(&self, claims: &[Claim], subject: &str) -> bool {
+        claims.iter().any(|c| {
+            c.verb == self.root.verb
+                && if subject.is_empty() {
+                    !c.is_global()
+                } else {
+                    c.subject.len() > subject.len()
+                        && c.subject.starts_with(subject)
+                        && c.subject[subject.len()..].starts_with('.')
+                }
+        })
+    }
+
+    /// Redacts the value at `subject`, which is not covered itself. Returns
+    /// whether the value should be kept.
+    fn redact_node(&self, claims: &[Claim], subject: &str, value: &mut Value) -> bool {
+        let walkable = matches!(value, Value::Object(_) | Value::Array(_));
+        if walkable && self.covers_under(claims, subject) {
+            self.redact_children(claims, subject, value);
+            return true;
+        }
+        self.deny(value)
+    }
+
+    fn deny(&self, value: &mut Value) -> bool {
+        match self.mode {
+            RedactMode::Remove => false,
+            RedactMode::Mask(ref mask) => {
+                *value = mask.clone();
+                true
+            }
+        }
+    }
+
+    fn redact_children(&self, claims: &[Claim], subject: &str, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                let fields = std::mem::take(map);
+                let mut kept = Map::new();
+                let parent = Claim::new(&self.root.verb, subject);
+                for (key, mut field) in fields {
+                    let keep = match claim_child(&parent, &key) {
+                        Ok(child) => {
+                            self.covers(claims, &child.subject)
+                                || self.redact_node(claims, &child.subject, &mut field)
+                        }
+                        // e.g. `a.b` would pass for a child of `a`
+                        Err(_) => self.deny(&mut field),
+                    };
+                    if keep {
+                        kept.insert(key, field);
+                    }
+                }
+                *map = kept;
+            }
+            Value::Array(items) => {
+                items.retain_mut(|item| self.redact_node(claims, subject, item));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims;
+    use serde_json::json;
+
+    fn user() -> Value {
+        json!({
+            "name": "Ada",
+            "email": "ada@example.com",
+            "address": {"city": "London", "street": "1 Main St"},
+            "tags": ["admin", "ops"],
+            "accounts": [
+                {"iban": "GB00", "bank": "First"},
+                {"iban": "GB01", "bank": "Second"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_covered_root() {
+        let redactor = Redactor::new("read", "users").unwrap();
+        for list in [&["read:users"][..], &["read:*"][..]].iter() {
+            let mut value = user();
+            redactor.redact_value(&claims(list), &mut value);
+            assert_eq!(value, user());
+        }
+    }
+
+    #[test]
+    fn test_nothing_covered() {
+        let redactor = Redactor::new("read", "users").unwrap();
+        let mut value = user();
+        redactor.redact_value(&claims(&["write:users", "read:users-x"]), &mut value);
+        assert_eq!(value, json!({}));
+    }
+
+    #[test]
+    fn test_scalar_root() {
+        let redactor = Redactor::new("read", "users").unwrap();
+        let masked = redactor.clone().with_mask(json!("***"));
+        let list = claims(&["read:users.name"]);
+
+        let mut value = json!("ada@example.com");
+        redactor.redact_value(&list, &mut value);
+        assert_eq!(value, Value::Null);
+
+        let mut value = json!(42);
+        masked.redact_value(&list, &mut value);
+        assert_eq!(value, json!("***"));
+
+        assert_eq!(
+            masked.redact(&claims(&["read:users"]), "Ada"),
+            Ok(json!("Ada"))
+        );
+        assert_eq!(redactor.redact(&list, "Ada"), Ok(Value::Null));
+    }
+
+    #[test]
+    fn test_nested_objects() {
+        let redactor = Redactor::new("read", "users").unwrap();
+        let mut value = user();
+        redactor.redact_value(
+            &claims(&[
+                "read:users.name",
+                "read:users.address.city",
+                "read:projects",
+            ]),
+            &mut value,
+        );
+        assert_eq!(value, json!({"name": "Ada", "address": {"city": "London"}}));
+    }
+
+    #[test]
+    fn test_arrays() {
+        let redactor = Redactor::new("read", "users").unwrap();
+        let mut value = user();
+        redactor.redact_value(
+            &claims(&["read:users.tags", "read:users.accounts.bank"]),
+            &mut value,
+        );
+        assert_eq!(
+            value,
+            json!({
+                "tags": ["admin", "ops"],
+                "accounts": [{"bank": "First"}, {"bank": "Second"}]
+            })
+        );
+
+        let mut list = json!([{"id": 1, "secret": "x"}, 7, {"id": 2}]);
+        redactor.redact_value(&claims(&["read:users.id"]), &mut list);
+        assert_eq!(list, json!([{"id": 1}, {"id": 2}]));
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        let redactor = Redactor::new("read", "users").unwrap();
+        let input = json!({
+            "profile": {"name": "Ada"},
+            "profile.ssn": "123-45",
+            "profile.": 1,
+            "": 2,
+            "a b": 3,
+            "nested": {"profile.ssn": "x", "ok": true}
+        });
+
+        let mut value = input.clone();
+        redactor.redact_value(
+            &claims(&["read:users.profile", "read:users.nested.ok"]),
+            &mut value,
+        );
+        assert_eq!(
+            value,
+            json!({"profile": {"name": "Ada"}, "nested": {"ok": true}})
+        );
+
+        let masker = redactor.clone().with_mask(json!(null));
+        let mut value = input.clone();
+        masker.redact_value(&claims(&["read:users.profile"]), &mut value);
+        assert_eq!(value["profile.ssn"], json!(null));
+        assert_eq!(value["profile"], json!({"name": "Ada"}));
+
+        // a claim covering the object covers every key in it
+        let mut value = input.clone();
+        redactor.redact_value(&claims(&["read:users"]), &mut value);
+        assert_eq!(value, input);
+
+        let root = Redactor::new("read", "").unwrap();
+        let mut value = json!({"-x": 1, "x": {"-y": 2}});
+        root.redact_value(&claims(&["read:x"]), &mut value);
+        assert_eq!(value, json!({"x": {"-y": 2}}));
+    }
+
+    #[test]
+    fn test_mask() {
+        let redactor = Redactor::new("read", "users")
+            .unwrap()
+            .with_mask(json!("***"));
+        assert_eq!(redactor.mode(), &RedactMode::Mask(json!("***")));
+
+        let mut value = user();
+        redactor.redact_value(
+            &claims(&["read:users.name", "read:users.accounts.iban"]),
+            &mut value,
+        );
+        assert_eq!(
+            value,
+            json!({
+                "name": "Ada",
+                "email": "***",
+                "address": "***",
+                "tags": "***",
+                "accounts": [
+                    {"iban": "GB00", "bank": "***"},
+                    {"iban": "GB01", "bank": "***"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_global_prefix() {
+        let redactor = Redactor::new("read", "").unwrap();
+        let mut value = json!({"users": {"name": "Ada", "email": "a@b"}, "billing": 3});
+        redactor.redact_value(&claims(&["read:users.name"]), &mut value);
+        assert_eq!(value, json!({"users": {"name": "Ada"}}));
+
+        assert!(Redactor::new("read", "bad prefix").is_err());
+    }
+
+    #[test]
+    fn test_serialize() {
+        #[derive(Serialize)]
+        struct Address {
+            city: String,
+            zip: String,
+        }
+
+        #[derive(Serialize)]
+        struct User {
+            name: String,
+            addresses: Vec<Address>,
+        }
+
+        let user = User {
+            name: "Ada".to_string(),
+            addresses: vec![Address {
+                city: "London".to_string(),
+                zip: "N1".to_string(),
+            }],
+        };
+        let redactor = Redactor::new("read", "users").unwrap();
+        assert_eq!(
+            redactor.redact(&claims(&["read:users.addresses.city"]), &user),
+            Ok(json!({"addresses": [{"city": "London"}]}))
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::audit::Decision;
+use crate::claim::{claim_check, claim_from_str, Claim};
+use crate::error::Error;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+
+/// A recorded decision that the new policy decides differently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flip {
+    /// 1-based line number in the log.
+    pub line: usize,
+    pub principal: String,
+    pub query: String,
+    pub old: Decision,
+    pub new: Decision,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlipCounts {
+    pub granted_to_denied: usize,
+    pub denied_to_granted: usize,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ReplayReport {
+    /// Number of decisions replayed.
+    pub total: usize,
+    /// Queries in the log that are not valid claims, which the new policy
+    /// always denies.
+    pub malformed: usize,
+    pub granted_to_denied: Vec<Flip>,
+    pub denied_to_granted: Vec<Flip>,
+    /// Flips counted by verb and subject prefix (`*` for global queries).
+    /// Malformed queries are not counted here.
+    pub counts: BTreeMap<(String, String), FlipCounts>,
+}
+
+/// Re-evaluates a log of past decisions against a new set of claims per
+/// principal, with `claim_check` semantics.
+///
+/// The log has a JSON object per line with at least `principal`, `query` and
+/// `decision` (`granted` or `denied`), as written by `JsonLinesSink`. Blank
+/// lines are ignored and principals missing from `policy` have no claims.
+/// Flips are counted under the first `prefix_depth` segments of the subject.
+pub fn replay<R: BufRead>(
+    log: R,
+    policy: &HashMap<String, Vec<Claim>>,
+    prefix_depth: usize,
+) -> Result<ReplayReport, Error> {
+    let mut report = ReplayReport::default();
+
+    for (idx, line) in log.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (principal, query, old) = parse_line(&line, idx + 1)?;
+        report.total += 1;
+
+        let parsed = claim_from_str(&query);
+        if parsed.is_err() {
+            report.malformed += 1;
+        }
+
+        let granted = match (&parsed, policy.get(&principal)) {
+            (Ok(q), Some(claims)) => claims.iter().any(|c| claim_check(c, q)),
+            _ => false,
+        };
+        let new = if granted {
+            Decision::Granted
+        } else {
+            Decision::Denied
+        };
+        if new == old {
+            continue;
+        }
+
+        if let Ok(q) = parsed {
+            let counts = report
+                .counts
+                .entry((q.verb.clone(), subject_prefix(&q, prefix_depth)))
+                .or_default();
+            if new == Decision::Denied {
+                counts.granted_to_denied += 1;
+            } else {
+                counts.denied_to_granted += 1;
+            }
+        }
+
+        let flip = Flip {
+            line: idx + 1,
+            principal,
+            query,
+            old,
+            new,
+        };
+        if new == Decision::Denied {
+            report.granted_to_denied.push(flip);
+        } else {
+            report.denied_to_granted.push(flip);
+        }
+    }
+
+    Ok(report)
+}
+
+fn parse_line(line: &str, number: usize) -> Result<(String, String, Decision), Error> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|e| Error::Syntax(format!("line {}: not valid JSON: {}", number, e)))?;
+
+    let field = |name: &str| match value.get(name) {
+        Some(Value::String(s)) => Ok(s.clone()),
+        _ => Err(Error::Syntax(format!(
+            "line {}: missing string field {}",
+            number, name
+        ))),
+    };
+
+    let decision = field("decision")?
+        .parse()
+        .map_err(|e| Error::Syntax(format!("line {}: {}", number, e)))?;
+    Ok((field("principal")?, field("query")?, decision))
+}
+
+fn subject_prefix(query: &Claim, depth: usize) -> String {
+    if query.is_global() {
+        return String::from("*");
+    }
+
+    let segments: Vec<&str> = query.subject.split('.').take(depth.max(1)).collect();
+    segments.join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims;
+
+    fn policy(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<Claim>> {
+        entries
+            .iter()
+            .map(|(p, c)| (p.to_string(), claims(c)))
+            .collect()
+    }
+
+    fn line(principal: &str, query: &str, decision: &str) -> String {
+        format!(
+            r#"{{"principal":"{}","query":"{}","decision":"{}"}}"#,
+            principal, query, decision
+        )
+    }
+
+    #[test]
+    fn test_replay_flips() {
+        let log = [
+            line("alice", "read:projects.1", "granted"),
+            line("alice", "read:projects.2.settings", "granted"),
+            line("alice", "write:projects.1", "denied"),
+            line("bob", "read:billing", "granted"),
+            line("bob", "read:projects.1", "denied"),
+            String::new(),
+            line("carol", "read:*", "granted"),
+        ]
+        .join("\n");
+
+        let new_policy = policy(&[
+            ("alice", &["read:projects.1", "write:projects"]),
+            ("bob", &["read:projects"]),
+        ]);
+        let report = replay(log.as_bytes(), &new_policy, 1).unwrap();
+
+        assert_eq!(report.total, 6);
+        assert_eq!(report.malformed, 0);
+
+        let g2d: Vec<(usize, &str)> = report
+            .granted_to_denied
+            .iter()
+            .map(|f| (f.line, f.query.as_str()))
+            .collect();
+        assert_eq!(
+            g2d,
+            vec![
+                (2, "read:projects.2.settings"),
+                (4, "read:billing"),
+                (7, "read:*")
+            ]
+        );
+
+        let d2g: Vec<(&str, &str)> = report
+            .denied_to_granted
+            .iter()
+            .map(|f| (f.principal.as_str(), f.query.as_str()))
+            .collect();
+        assert_eq!(
+            d2g,
+            vec![("alice", "write:projects.1"), ("bob", "read:projects.1")]
+        );
+
+        let counts: Vec<(&str, &str, usize, usize)> = report
+            .counts
+            .iter()
+            .map(|((v, p), c)| {
+                (
+                    v.as_str(),
+                    p.as_str(),
+                    c.granted_to_denied,
+                    c.denied_to_granted,
+                )
+            })
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("read", "*", 1, 0),
+                ("read", "billing", 1, 0),
+                ("read", "projects", 1, 1),
+                ("write", "projects", 0, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_prefix_depth() {
+        let log = [
+            line("alice", "read:projects.1.a", "granted"),
+            line("alice", "read:projects.2", "granted"),
+        ]
+        .join("\n");
+        let report = replay(log.as_bytes(), &HashMap::new(), 2).unwrap();
+
+        let keys: Vec<&str> = report.counts.keys().map(|(_, p)| p.as_str()).collect();
+        assert_eq!(keys, vec!["projects.1", "projects.2"]);
+    }
+
+    #[test]
+    fn test_replay_malformed_query() {
+        let log = [
+            line("alice", "garbage", "granted"),
+            line("alice", "garbage", "denied"),
+        ]
+        .join("\n");
+        let new_policy = policy(&[("alice", &["read:*"])]);
+        let report = replay(log.as_bytes(), &new_policy, 1).unwrap();
+
+        assert_eq!(report.total, 2);
+        assert_eq!(report.malformed, 2);
+        assert_eq!(report.granted_to_denied.len(), 1);
+        assert!(report.denied_to_granted.is_empty());
+        assert!(report.counts.is_empty());
+    }
+
+    #[test]
+    fn test_replay_malformed_log() {
+        let new_policy = HashMap::new();
+
+        let log = format!("{}\nnot json", line("a", "read:*", "granted"));
+        let err = replay(log.as_bytes(), &new_policy, 1).unwrap_err();
+        assert!(err.to_string().starts_with("line 2: not valid JSON"));
+
+        let log = r#"{"principal":"a","query":"read:*"}"#;
+        assert_eq!(
+            replay(log.as_bytes(), &new_policy, 1),
+            Err(Error::Syntax(
+                "line 1: missing string field decision".to_string()
+            ))
+        );
+
+        let log = line("a", "read:*", "maybe");
+        assert_eq!(
+            replay(log.as_bytes(), &new_policy, 1),
+            Err(Error::Syntax(
+                "line 1: the given decision maybe is not valid".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_replay_audit_log() {
+        use crate::audit::{Auditor, JsonLinesSink};
+
+        let old_claims = claims(&["read:*"]);
+        let auditor = Auditor::new(JsonLinesSink::new(Vec::new()));
+        for query in ["read:a", "read:b.c", "write:a"].iter() {
+            auditor
+                .check_str(old_claims.iter(), query, "alice", None)
+                .unwrap();
+        }
+        let log = auditor.into_sink().into_inner();
+
+        let new_policy = policy(&[("alice", &["read:a", "write:a"])]);
+        let report = replay(log.as_slice(), &new_policy, 1).unwrap();
+        assert_eq!(report.total, 3);
+        assert_eq!(report.granted_to_denied[0].query, "read:b.c");
+        assert_eq!(report.denied_to_granted[0].query, "write:a");
+    }
+}
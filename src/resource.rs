@@ -0,0 +1,157 @@
+use crate::claim::{claim_check_parts, Claim};
+use std::borrow::Cow;
+
+/// A value that claims can be checked against, through its subject, e.g. a
+/// project stored under `projects.42`.
+pub trait Resource {
+    /// The subject of the value, empty for a global one. Borrow it when the
+    /// value already stores it to avoid allocating on every check.
+    fn subject(&self) -> Cow<'_, str>;
+}
+
+impl Resource for str {
+    fn subject(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl Resource for String {
+    fn subject(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl Resource for Claim {
+    fn subject(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.subject)
+    }
+}
+
+impl<T: Resource + ?Sized> Resource for &T {
+    fn subject(&self) -> Cow<'_, str> {
+        (**self).subject()
+    }
+}
+
+/// Whether any of the claims covers the resource for the verb, with
+/// `claim_check` semantics.
+pub fn resource_check<R: Resource + ?Sized>(claims: &[Claim], verb: &str, resource: &R) -> bool {
+    let subject = resource.subject();
+    claims.iter().any(|c| claim_check_parts(c, verb, &subject))
+}
+
+/// Keeps the resources that the claims cover for the verb.
+pub fn resources_filter<'a, I, R>(
+    claims: &'a [Claim],
+    verb: &'a str,
+    resources: I,
+) -> impl Iterator<Item = R> + 'a
+where
+    I: Iterator<Item = R> + 'a,
+    R: Resource,
+{
+    resources.filter(move |r| resource_check(claims, verb, r))
+}
+
+/// Splits the resources into those the claims cover for the verb and the
+/// rest, keeping their order.
+pub fn resources_partition<I, R>(claims: &[Claim], verb: &str, resources: I) -> (Vec<R>, Vec<R>)
+where
+    I: Iterator<Item = R>,
+    R: Resource,
+{
+    resources.partition(|r| resource_check(claims, verb, r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claim::claim_check;
+    use crate::test_util::claims;
+
+    struct Project {
+        id: u32,
+        path: String,
+    }
+
+    impl Resource for Project {
+        fn subject(&self) -> Cow<'_, str> {
+            Cow::Borrowed(&self.path)
+        }
+    }
+
+    struct Invoice(u32);
+
+    impl Resource for Invoice {
+        fn subject(&self) -> Cow<'_, str> {
+            Cow::Owned(format!("invoices.{}", self.0))
+        }
+    }
+
+    fn projects() -> Vec<Project> {
+        [
+            "projects.1",
+            "projects.1.settings",
+            "projects.12",
+            "users.1",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(id, path)| Project {
+            id: id as u32,
+            path: path.to_string(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_check() {
+        let claims = claims(&["read:projects.1", "write:*"]);
+        assert!(resource_check(&claims, "read", "projects.1.x"));
+        assert!(!resource_check(&claims, "read", "projects.12"));
+        assert!(resource_check(&claims, "write", &String::from("")));
+        assert!(resource_check(&claims, "write", &Invoice(3)));
+        assert!(!resource_check(&claims, "read", &Invoice(3)));
+        assert!(!resource_check(&[], "read", "projects"));
+    }
+
+    #[test]
+    fn test_filter() {
+        let claims = claims(&["read:projects.1", "read:users"]);
+        let items = projects();
+
+        let ids: Vec<u32> = resources_filter(&claims, "read", items.iter())
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, vec![0, 1, 3]);
+
+        let ids: Vec<u32> = resources_filter(&claims, "write", items.iter())
+            .map(|p| p.id)
+            .collect();
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    fn test_partition() {
+        let claims = claims(&["read:projects.1"]);
+        let (allowed, denied) = resources_partition(&claims, "read", projects().into_iter());
+        let allowed: Vec<u32> = allowed.iter().map(|p| p.id).collect();
+        let denied: Vec<u32> = denied.iter().map(|p| p.id).collect();
+        assert_eq!(allowed, vec![0, 1]);
+        assert_eq!(denied, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_consistent_with_claim_check() {
+        let claims = claims(&["read:a", "read:b.c", "read:d-e"]);
+        for subject in ["a", "a.b", "ab", "b", "b.c", "b.cd", "d-e.f", "d", ""].iter() {
+            let query = Claim::new("read", subject);
+            assert_eq!(
+                resource_check(&claims, "read", &query),
+                claims.iter().any(|c| claim_check(c, &query)),
+                "subject {}",
+                subject
+            );
+        }
+    }
+}
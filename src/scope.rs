@@ -0,0 +1,213 @@
+use crate::claim::{claims_from_strs, is_valid_claim_str, Claim};
+use crate::error::Error;
+
+/// What to do with scope entries that are not valid claim strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidEntryPolicy {
+    /// Fail with the syntax error of the first invalid entry.
+    Reject,
+    /// Ignore invalid entries.
+    Skip,
+    /// Ignore invalid entries but return them in `ParsedScope::invalid`.
+    Collect,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParsedScope {
+    /// Sorted and deduplicated, as returned by `claims_from_strs`.
+    pub claims: Vec<Claim>,
+    /// Entries rejected by the `Collect` policy, in their original order.
+    pub invalid: Vec<String>,
+}
+
+/// Parses a space-separated OAuth `scope` string.
+pub fn claims_from_scope(scope: &str, policy: InvalidEntryPolicy) -> Result<ParsedScope, Error> {
+    claims_from_entries(scope.split_whitespace(), policy)
+}
+
+/// Parses a JWT scope claim, given as JSON: either an array of claim strings
+/// or a single space-separated string.
+#[cfg(feature = "json")]
+pub fn claims_from_json_scope(
+    json: &str,
+    policy: InvalidEntryPolicy,
+) -> Result<ParsedScope, Error> {
+    use serde_json::Value;
+
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| Error::Syntax(format!("the given scope is not valid JSON: {}", e)))?;
+
+    match value {
+        Value::String(scope) => claims_from_scope(&scope, policy),
+        Value::Array(values) => {
+            // non-string entries keep their JSON text, which is never a valid claim
+            let entries: Vec<String> = values
+                .into_iter()
+                .map(|v| match v {
+                    Value::String(entry) => entry,
+                    _ => v.to_string(),
+                })
+                .collect();
+            claims_from_entries(entries.iter().map(|e| e.as_str()), policy)
+        }
+        _ => Err(Error::Syntax(format!(
+            "the given scope {} is not a string or an array",
+            value
+        ))),
+    }
+}
+
+/// Renders claims as a canonical scope string: sorted, deduplicated and
+/// space-separated, so that `claims_from_scope` gives back the same list.
+pub fn scope_from_claims<'a, I>(claims: I) -> String
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let mut list: Vec<&Claim> = claims.collect();
+    list.sort();
+    list.dedup();
+
+    let strings: Vec<String> = list.iter().map(|c| c.to_string()).collect();
+    strings.join(" ")
+}
+
+fn claims_from_entries<'a, I>(entries: I, policy: InvalidEntryPolicy) -> Result<ParsedScope, Error>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let mut valid = Vec::new();
+    let mut invalid = Vec::new();
+    for entry in entries {
+        if policy == InvalidEntryPolicy::Reject || is_valid_claim_str(entry) {
+            valid.push(entry);
+        } else if policy == InvalidEntryPolicy::Collect {
+            invalid.push(String::from(entry));
+        }
+    }
+
+    Ok(ParsedScope {
+        claims: claims_from_strs(valid.iter())?,
+        invalid,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims;
+
+    #[test]
+    fn test_scope_valid() {
+        let parsed = claims_from_scope(
+            "  read:b admin:*  read:a read:b ",
+            InvalidEntryPolicy::Reject,
+        );
+        assert_eq!(
+            parsed,
+            Ok(ParsedScope {
+                claims: claims(&["admin:*", "read:a", "read:b"]),
+                invalid: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_scope_blank() {
+        let parsed = claims_from_scope("", InvalidEntryPolicy::Reject).unwrap();
+        assert!(parsed.claims.is_empty());
+        assert!(parsed.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_scope_policies() {
+        let scope = "openid read:a profile read:b.*";
+
+        assert_eq!(
+            claims_from_scope(scope, InvalidEntryPolicy::Reject),
+            Err(Error::Syntax(
+                "the given claim openid is not valid".to_string()
+            ))
+        );
+        assert_eq!(
+            claims_from_scope(scope, InvalidEntryPolicy::Skip),
+            Ok(ParsedScope {
+                claims: claims(&["read:a", "read:b"]),
+                invalid: Vec::new(),
+            })
+        );
+        assert_eq!(
+            claims_from_scope(scope, InvalidEntryPolicy::Collect),
+            Ok(ParsedScope {
+                claims: claims(&["read:a", "read:b"]),
+                invalid: vec!["openid".to_string(), "profile".to_string()],
+            })
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_array() {
+        let json = r#"["read:a", "email", 42, "admin:*"]"#;
+
+        assert!(claims_from_json_scope(json, InvalidEntryPolicy::Reject).is_err());
+        assert_eq!(
+            claims_from_json_scope(json, InvalidEntryPolicy::Skip),
+            Ok(ParsedScope {
+                claims: claims(&["admin:*", "read:a"]),
+                invalid: Vec::new(),
+            })
+        );
+        assert_eq!(
+            claims_from_json_scope(json, InvalidEntryPolicy::Collect),
+            Ok(ParsedScope {
+                claims: claims(&["admin:*", "read:a"]),
+                invalid: vec!["email".to_string(), "42".to_string()],
+            })
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_string() {
+        assert_eq!(
+            claims_from_json_scope(r#""read:a admin:*""#, InvalidEntryPolicy::Reject),
+            Ok(ParsedScope {
+                claims: claims(&["admin:*", "read:a"]),
+                invalid: Vec::new(),
+            })
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_invalid() {
+        assert!(claims_from_json_scope("[", InvalidEntryPolicy::Skip).is_err());
+        assert_eq!(
+            claims_from_json_scope("{}", InvalidEntryPolicy::Skip),
+            Err(Error::Syntax(
+                "the given scope {} is not a string or an array".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let list = [
+            Claim::new("read", "b"),
+            Claim::new("admin", ""),
+            Claim::new("read", "a"),
+            Claim::new("read", "b"),
+        ];
+        assert_eq!(scope_from_claims(list.iter()), "admin:* read:a read:b");
+        assert_eq!(scope_from_claims([].iter()), "");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let list = claims(&["admin-x:a", "admin:*", "read:a.b", "read:a", "read:a-b"]);
+        let scope = scope_from_claims(list.iter());
+        let parsed = claims_from_scope(&scope, InvalidEntryPolicy::Reject).unwrap();
+        assert_eq!(parsed.claims, list);
+        assert_eq!(scope_from_claims(parsed.claims.iter()), scope);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::claim::{claim_check, claim_from_parts, claims_minimize, Claim};
+use crate::error::Error;
+
+/// A parameterized SQL predicate: `sql` has a `?` placeholder per entry of
+/// `params`, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlPredicate {
+    pub sql: String,
+    pub params: Vec<String>,
+}
+
+/// Translates claims into SQL predicates over a column holding dotted paths
+/// of the subjects under `prefix`, e.g. with prefix `projects` a row with
+/// path `42.settings` stands for the subject `projects.42.settings`.
+///
+/// The predicate selects the rows whose subject `claim_check` allows for the
+/// verb. Descendants of `x` are matched with the range `column >= 'x.' AND
+/// column < 'x/'` (`/` follows `.`), which can use an index on the column,
+/// instead of `LIKE`, which ignores ASCII case by default in SQLite. The
+/// column must use a byte-wise collation such as SQLite's default `BINARY`
+/// for the range to be exact and case-sensitive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlFilter {
+    prefix: Claim,
+    column: String,
+}
+
+impl SqlFilter {
+    /// The column is written into the SQL as given, so it must come from
+    /// trusted code. An empty prefix means the column holds full subjects.
+    pub fn new(verb: &str, prefix: &str, column: &str) -> Result<Self, Error> {
+        Ok(SqlFilter {
+            prefix: claim_from_parts(verb, prefix)?,
+            column: String::from(column),
+        })
+    }
+
+    /// `1=1` if a claim covers the whole prefix, `1=0` if no claim covers
+    /// anything under it, and otherwise an `OR` of an equality and a range
+    /// per (minimized) claim.
+    pub fn where_clause<'a, I>(&self, claims: I) -> SqlPredicate
+    where
+        I: Iterator<Item = &'a Claim>,
+    {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+
+        for claim in claims_minimize(claims.filter(|c| c.verb == self.prefix.verb)) {
+            if claim_check(&claim, &self.prefix) {
+                return SqlPredicate {
+                    sql: String::from("1=1"),
+                    params: Vec::new(),
+                };
+            }
+            if !claim_check(&self.prefix, &claim) {
+                continue;
+            }
+
+            let path = if self.prefix.is_global() {
+                claim.subject.as_str()
+            } else {
+                &claim.subject[self.prefix.subject.len() + 1..]
+            };
+            conditions.push(format!(
+                "{col} = ? OR ({col} >= ? AND {col} < ?)",
+                col = self.column
+            ));
+            params.push(String::from(path));
+            params.push(format!("{}.", path));
+            params.push(format!("{}/", path));
+        }
+
+        if conditions.is_empty() {
+            return SqlPredicate {
+                sql: String::from("1=0"),
+                params,
+            };
+        }
+        SqlPredicate {
+            sql: format!("({})", conditions.join(" OR ")),
+            params,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims;
+    use rusqlite::{params_from_iter, Connection};
+
+    const PATHS: [&str; 19] = [
+        "1",
+        "1.settings",
+        "1-x",
+        "1/",
+        "1/x",
+        "10",
+        "1_a",
+        "1xa.b",
+        "1%",
+        "1%.x",
+        "a_b",
+        "a_b.c",
+        "A_B",
+        "secret.x",
+        "SECRET.x",
+        "Secret.y",
+        "ñ.x",
+        "Ñ.x",
+        "ña",
+    ];
+
+    fn database() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE projects (path TEXT NOT NULL);
+             CREATE INDEX projects_path ON projects (path);",
+        )
+        .unwrap();
+        for path in PATHS.iter() {
+            conn.execute("INSERT INTO projects (path) VALUES (?)", [path])
+                .unwrap();
+        }
+        conn
+    }
+
+    fn select(conn: &Connection, predicate: &SqlPredicate) -> Vec<String> {
+        let sql = format!(
+            "SELECT path FROM projects WHERE {} ORDER BY path",
+            predicate.sql
+        );
+        let mut stmt = conn.prepare(&sql).unwrap();
+        let rows = stmt
+            .query_map(params_from_iter(predicate.params.iter()), |row| row.get(0))
+            .unwrap();
+        rows.map(|r| r.unwrap()).collect()
+    }
+
+    fn expected(list: &[Claim]) -> Vec<String> {
+        let mut paths: Vec<String> = PATHS
+            .iter()
+            .filter(|path| {
+                let query = Claim::new("read", &format!("projects.{}", path));
+                list.iter().any(|c| claim_check(c, &query))
+            })
+            .map(|path| path.to_string())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_predicate() {
+        let filter = SqlFilter::new("read", "projects", "path").unwrap();
+
+        let list = claims(&["read:projects.1_a", "read:projects.1", "read:projects.1.x"]);
+        assert_eq!(
+            filter.where_clause(list.iter()),
+            SqlPredicate {
+                sql:
+                    "(path = ? OR (path >= ? AND path < ?) OR path = ? OR (path >= ? AND path < ?))"
+                        .to_string(),
+                params: vec![
+                    "1".to_string(),
+                    "1.".to_string(),
+                    "1/".to_string(),
+                    "1_a".to_string(),
+                    "1_a.".to_string(),
+                    "1_a/".to_string()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_predicate_true_and_false() {
+        let filter = SqlFilter::new("read", "projects", "path").unwrap();
+        let none = SqlPredicate {
+            sql: "1=0".to_string(),
+            params: Vec::new(),
+        };
+        let all = SqlPredicate {
+            sql: "1=1".to_string(),
+            params: Vec::new(),
+        };
+
+        assert_eq!(filter.where_clause(claims(&[]).iter()), none);
+        assert_eq!(
+            filter.where_clause(claims(&["write:*", "read:users", "read:projects-x"]).iter()),
+            none
+        );
+        assert_eq!(filter.where_clause(claims(&["read:*"]).iter()), all);
+        assert_eq!(
+            filter.where_clause(claims(&["read:projects.1", "read:projects"]).iter()),
+            all
+        );
+    }
+
+    #[test]
+    fn test_global_prefix() {
+        let filter = SqlFilter::new("read", "", "path").unwrap();
+        let predicate = filter.where_clause(claims(&["read:projects.a_b"]).iter());
+        assert_eq!(predicate.sql, "(path = ? OR (path >= ? AND path < ?))");
+        assert_eq!(
+            predicate.params,
+            vec!["projects.a_b", "projects.a_b.", "projects.a_b/"]
+        );
+        assert!(SqlFilter::new("read", "bad prefix", "path").is_err());
+    }
+
+    #[test]
+    fn test_against_sqlite() {
+        let conn = database();
+        let filter = SqlFilter::new("read", "projects", "path").unwrap();
+
+        let cases: [&[&str]; 10] = [
+            &[],
+            &["read:*"],
+            &["read:projects.1"],
+            &["read:projects.1_a", "read:projects.1-a"],
+            &["read:projects.a_b", "read:projects.1.settings"],
+            &["read:projects.A_B", "write:projects.1"],
+            &["read:projects.1x", "read:projects.10", "read:users"],
+            &["read:projects.secret"],
+            &["read:projects.ñ"],
+            &["read:projects.Ñ", "read:projects.a_b.C"],
+        ];
+        for case in cases.iter() {
+            let list = claims(case);
+            let predicate = filter.where_clause(list.iter());
+            assert_eq!(
+                select(&conn, &predicate),
+                expected(&list),
+                "claims {:?} with {:?}",
+                case,
+                predicate
+            );
+        }
+    }
+
+    #[test]
+    fn test_uses_index() {
+        let conn = database();
+        let filter = SqlFilter::new("read", "projects", "path").unwrap();
+        let list = claims(&["read:projects.1", "read:projects.a_b"]);
+        let predicate = filter.where_clause(list.iter());
+
+        let sql = format!(
+            "EXPLAIN QUERY PLAN SELECT path FROM projects WHERE {}",
+            predicate.sql
+        );
+        let mut stmt = conn.prepare(&sql).unwrap();
+        let plan: Vec<String> = stmt
+            .query_map(params_from_iter(predicate.params.iter()), |row| row.get(3))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert!(
+            plan.iter().all(|step| !step.starts_with("SCAN")),
+            "{:?}",
+            plan
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use crate::claim::{claim_from_str, claims_from_strs, Claim};
+
+/// Parses the claims, sorted and deduplicated like `claims_from_strs`.
+pub(crate) fn claims(list: &[&str]) -> Vec<Claim> {
+    claims_from_strs(list.iter()).unwrap()
+}
+
+/// Parses the claims keeping their order and duplicates.
+pub(crate) fn claims_in_order(list: &[&str]) -> Vec<Claim> {
+    list.iter().map(|c| claim_from_str(c).unwrap()).collect()
+}
+
+pub(crate) fn strings(list: &[Claim]) -> Vec<String> {
+    list.iter().map(|c| c.to_string()).collect()
+}
@@ -0,0 +1,296 @@
+use crate::claim::{claim_check, parse_query, Claim};
+use crate::error::Error;
+use crate::scope::{claims_from_scope, scope_from_claims, InvalidEntryPolicy};
+use crate::token::{err_malformed, mac_for, signature_for, TokenError};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::Mac;
+
+const CHAIN_VERSION: &str = "claims-chain-v1";
+
+/// Claims verified from a chained credential: a first block of claims signed
+/// with the issuer's key, followed by blocks that can only narrow it.
+///
+/// The signed form is `claims-chain-v1.<block>...<block>.<signature>`, each
+/// block being a canonical scope string in base64url without padding. The
+/// signature of each block is the HMAC-SHA256 of the block keyed with the
+/// signature of the previous one, so anyone holding a credential can
+/// attenuate it but only the issuer can verify it.
+#[derive(Debug, PartialEq)]
+pub struct ChainedCredential {
+    blocks: Vec<Vec<Claim>>,
+}
+
+impl ChainedCredential {
+    /// All the blocks, from the one minted by the issuer to the last
+    /// attenuation.
+    pub fn blocks(&self) -> &[Vec<Claim>] {
+        &self.blocks
+    }
+
+    /// The claims of the last block, which are covered by every other block.
+    pub fn effective_claims(&self) -> &[Claim] {
+        self.blocks.last().map(|b| b.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn check(&self, query: &Claim) -> bool {
+        self.effective_claims()
+            .iter()
+            .any(|c| claim_check(c, query))
+    }
+
+    pub fn check_str(&self, query: &str) -> bool {
+        match parse_query(query) {
+            Ok(parsed) => self.check(&parsed),
+            Err(_) => false,
+        }
+    }
+
+    /// Verifies the signature chain with the issuer's key and that every
+    /// block is covered by the previous one.
+    pub fn verify(token: &str, key: &[u8]) -> Result<Self, TokenError> {
+        let (encoded_blocks, signature) = split_token(token)?;
+
+        let mut block_signature = signature_for(CHAIN_VERSION.as_bytes(), key);
+        let (last, rest) = encoded_blocks.split_last().expect("at least one block");
+        for encoded in rest.iter() {
+            block_signature = signature_for(encoded.as_bytes(), &block_signature);
+        }
+        let mut mac = mac_for(&block_signature);
+        mac.update(last.as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| TokenError::BadSignature)?;
+
+        let mut blocks: Vec<Vec<Claim>> = Vec::with_capacity(encoded_blocks.len());
+        for encoded in encoded_blocks.iter() {
+            let block = decode_block(encoded)?;
+            if let Some(previous) = blocks.last() {
+                ensure_covered(previous, &block)?;
+            }
+            blocks.push(block);
+        }
+
+        Ok(Self { blocks })
+    }
+}
+
+/// Creates a chained credential with a single block of claims, signed with
+/// the issuer's key.
+pub fn mint<'a, I>(claims: I, key: &[u8]) -> Result<String, Error>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let encoded = encode_block(claims)?;
+    let signature = signature_for(CHAIN_VERSION.as_bytes(), key);
+    let signature = signature_for(encoded.as_bytes(), &signature);
+    Ok(format!(
+        "{}.{}.{}",
+        CHAIN_VERSION,
+        encoded,
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+/// Appends a block of claims to a chained credential without the issuer's
+/// key. Every claim must be covered by the claims of the current last block.
+pub fn attenuate<'a, I>(token: &str, claims: I) -> Result<String, TokenError>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let (encoded_blocks, signature) = split_token(token)?;
+    let previous = decode_block(encoded_blocks.last().expect("at least one block"))?;
+
+    let encoded = encode_block(claims).map_err(TokenError::InvalidClaim)?;
+    ensure_covered(&previous, &decode_block(&encoded)?)?;
+
+    let signature = signature_for(encoded.as_bytes(), &signature);
+    Ok(format!(
+        "{}.{}.{}.{}",
+        CHAIN_VERSION,
+        encoded_blocks.join("."),
+        encoded,
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+fn split_token(token: &str) -> Result<(Vec<&str>, Vec<u8>), TokenError> {
+    let mut parts: Vec<&str> = token.split('.').collect();
+    if parts.len() < 3 || parts[0] != CHAIN_VERSION {
+        return Err(err_malformed("the token is not a chained credential"));
+    }
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(parts.pop().unwrap_or_default())
+        .map_err(|_| err_malformed("the token signature is not base64url"))?;
+    parts.remove(0);
+    Ok((parts, signature))
+}
+
+fn encode_block<'a, I>(claims: I) -> Result<String, Error>
+where
+    I: Iterator<Item = &'a Claim>,
+{
+    let scope = scope_from_claims(claims);
+    claims_from_scope(&scope, InvalidEntryPolicy::Reject)?;
+    Ok(URL_SAFE_NO_PAD.encode(scope.as_bytes()))
+}
+
+fn decode_block(encoded: &str) -> Result<Vec<Claim>, TokenError> {
+    let scope = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| err_malformed("the token block is not base64url UTF-8"))?;
+
+    claims_from_scope(&scope, InvalidEntryPolicy::Reject)
+        .map(|parsed| parsed.claims)
+        .map_err(TokenError::InvalidClaim)
+}
+
+fn ensure_covered(previous: &[Claim], block: &[Claim]) -> Result<(), TokenError> {
+    match block
+        .iter()
+        .find(|c| !previous.iter().any(|p| claim_check(p, c)))
+    {
+        Some(claim) => Err(TokenError::NotCovered(claim.to_string())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims;
+
+    const KEY: &[u8] = b"secret-key";
+
+    #[test]
+    fn test_mint_and_verify() {
+        let token = mint(claims(&["read:*", "write:projects"]).iter(), KEY).unwrap();
+        let credential = ChainedCredential::verify(&token, KEY).unwrap();
+
+        assert_eq!(credential.blocks().len(), 1);
+        assert_eq!(
+            credential.effective_claims(),
+            claims(&["read:*", "write:projects"]).as_slice()
+        );
+        assert!(credential.check_str("read:anything"));
+        assert!(credential.check_str("write:projects.42"));
+        assert!(!credential.check_str("write:users"));
+    }
+
+    #[test]
+    fn test_attenuate() {
+        let token = mint(claims(&["read:*", "write:projects"]).iter(), KEY).unwrap();
+        let narrowed = attenuate(
+            &token,
+            claims(&["read:projects", "write:projects.42"]).iter(),
+        )
+        .unwrap();
+        let narrowest = attenuate(&narrowed, claims(&["read:projects.42"]).iter()).unwrap();
+
+        let credential = ChainedCredential::verify(&narrowest, KEY).unwrap();
+        assert_eq!(credential.blocks().len(), 3);
+        assert!(credential.check_str("read:projects.42.settings"));
+        assert!(!credential.check_str("read:projects.43"));
+        assert!(!credential.check_str("write:projects.42"));
+        assert!(!credential.check(&Claim::new("read", "users")));
+    }
+
+    #[test]
+    fn test_attenuate_to_nothing() {
+        let token = mint(claims(&["read:*"]).iter(), KEY).unwrap();
+        let empty = attenuate(&token, [].iter()).unwrap();
+
+        let credential = ChainedCredential::verify(&empty, KEY).unwrap();
+        assert!(credential.effective_claims().is_empty());
+        assert!(!credential.check_str("read:anything"));
+    }
+
+    #[test]
+    fn test_attenuate_cannot_widen() {
+        let token = mint(claims(&["read:projects"]).iter(), KEY).unwrap();
+        assert_eq!(
+            attenuate(&token, claims(&["read:projects.1", "read:*"]).iter()),
+            Err(TokenError::NotCovered("read:*".to_string()))
+        );
+        assert_eq!(
+            attenuate(&token, claims(&["write:projects"]).iter()),
+            Err(TokenError::NotCovered("write:projects".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_forged_widening() {
+        let token = mint(claims(&["read:projects"]).iter(), KEY).unwrap();
+        let (blocks, signature) = split_token(&token).unwrap();
+        let widened = URL_SAFE_NO_PAD.encode("read:*");
+        let forged = format!(
+            "{}.{}.{}.{}",
+            CHAIN_VERSION,
+            blocks[0],
+            widened,
+            URL_SAFE_NO_PAD.encode(signature_for(widened.as_bytes(), &signature))
+        );
+
+        assert_eq!(
+            ChainedCredential::verify(&forged, KEY),
+            Err(TokenError::NotCovered("read:*".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_verify_bad_signature() {
+        let token = mint(claims(&["read:projects"]).iter(), KEY).unwrap();
+        let narrowed = attenuate(&token, claims(&["read:projects.1"]).iter()).unwrap();
+        assert_eq!(
+            ChainedCredential::verify(&narrowed, b"other-key"),
+            Err(TokenError::BadSignature)
+        );
+
+        // dropping the last block does not give back the original signature
+        let mut parts: Vec<&str> = narrowed.split('.').collect();
+        parts.remove(2);
+        assert_eq!(
+            ChainedCredential::verify(&parts.join("."), KEY),
+            Err(TokenError::BadSignature)
+        );
+
+        let other = mint(claims(&["read:*"]).iter(), KEY).unwrap();
+        let swapped = format!(
+            "{}.{}",
+            other.rsplit_once('.').unwrap().0,
+            token.rsplit('.').next().unwrap()
+        );
+        assert_eq!(
+            ChainedCredential::verify(&swapped, KEY),
+            Err(TokenError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let list = [
+            "",
+            "claims-chain-v1",
+            "claims-chain-v1.abc",
+            "other.abc.def",
+        ];
+        for token in list.iter() {
+            assert!(matches!(
+                ChainedCredential::verify(token, KEY),
+                Err(TokenError::Malformed(_))
+            ));
+            assert!(matches!(
+                attenuate(token, [].iter()),
+                Err(TokenError::Malformed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_mint_invalid_claim() {
+        let list = [Claim::new("bad verb", "x")];
+        assert!(mint(list.iter(), KEY).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+mod chained;
+
+use crate::claim::Claim;
+use crate::error::Error;
+use crate::scope::{claims_from_scope, scope_from_claims, InvalidEntryPolicy};
+pub use crate::token::chained::{attenuate, mint, ChainedCredential};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const VERSION: &str = "claims-v1";
+
+/// An error that occurred while verifying a capability token.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TokenError {
+    /// The token is not made of a payload and a signature, or the payload
+    /// is not in the expected format.
+    Malformed(String),
+
+    /// The signature does not match the payload for the given key.
+    BadSignature,
+
+    /// The token expired at the given unix timestamp, in seconds.
+    Expired(u64),
+
+    /// One of the claims in the payload is not valid.
+    InvalidClaim(Error),
+
+    /// A claim in an attenuated block is not covered by the previous block.
+    NotCovered(String),
+}
+
+impl ::std::error::Error for TokenError {}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TokenError::Malformed(ref err) => err.fmt(f),
+            TokenError::BadSignature => write!(f, "the token signature is not valid"),
+            TokenError::Expired(at) => write!(f, "the token expired at {}", at),
+            TokenError::InvalidClaim(ref err) => err.fmt(f),
+            TokenError::NotCovered(ref claim) => write!(
+                f,
+                "the claim {} is not covered by the previous block",
+                claim
+            ),
+        }
+    }
+}
+
+/// A self-contained list of claims issued by `issuer` and valid until
+/// `expires_at` (unix timestamp, in seconds), signed with HMAC-SHA256.
+///
+/// The signed form is `<payload>.<signature>`, both base64url without
+/// padding, the signature being the HMAC of the encoded payload. The payload
+/// has four lines: the version (`claims-v1`), the issuer, the expiry and the
+/// claims as a single canonical scope string (sorted, deduplicated and
+/// space-separated, see `scope_from_claims`), empty when there are no claims.
+#[derive(Debug, PartialEq)]
+pub struct CapabilityToken {
+    issuer: String,
+    expires_at: u64,
+    claims: Vec<Claim>,
+}
+
+impl CapabilityToken {
+    /// Fails if the issuer contains a line break.
+    pub fn new(issuer: &str, expires_at: u64, claims: Vec<Claim>) -> Result<Self, Error> {
+        if issuer.contains(['\n', '\r']) {
+            return Err(Error::Syntax(format!(
+                "the given issuer {:?} is not valid",
+                issuer
+            )));
+        }
+
+        let scope = scope_from_claims(claims.iter());
+        let parsed = claims_from_scope(&scope, InvalidEntryPolicy::Reject)?;
+        Ok(Self {
+            issuer: String::from(issuer),
+            expires_at,
+            claims: parsed.claims,
+        })
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    pub fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+
+    /// Sorted and deduplicated.
+    pub fn claims(&self) -> &[Claim] {
+        &self.claims
+    }
+
+    pub fn into_claims(self) -> Vec<Claim> {
+        self.claims
+    }
+
+    pub fn sign(&self, key: &[u8]) -> String {
+        let payload = format!(
+            "{}\n{}\n{}\n{}",
+            VERSION,
+            self.issuer,
+            self.expires_at,
+            scope_from_claims(self.claims.iter())
+        );
+        let encoded = URL_SAFE_NO_PAD.encode(payload.as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(signature_for(encoded.as_bytes(), key));
+        format!("{}.{}", encoded, signature)
+    }
+
+    /// Verifies the signature and the expiry against `now` (unix timestamp,
+    /// in seconds) and returns the token.
+    pub fn verify(token: &str, key: &[u8], now: u64) -> Result<Self, TokenError> {
+        let mut parts = token.split('.');
+        let (encoded, signature) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(encoded), Some(signature), None) => (encoded, signature),
+            _ => return Err(err_malformed("the token does not have two parts")),
+        };
+
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| err_malformed("the token signature is not base64url"))?;
+        let mut mac = mac_for(key);
+        mac.update(encoded.as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| TokenError::BadSignature)?;
+
+        let payload = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| err_malformed("the token payload is not base64url UTF-8"))?;
+
+        let lines: Vec<&str> = payload.split('\n').collect();
+        if lines.len() != 4 || lines[0] != VERSION {
+            return Err(err_malformed("the token payload is not in a known format"));
+        }
+
+        let expires_at = lines[2]
+            .parse::<u64>()
+            .map_err(|_| err_malformed("the token expiry is not a timestamp"))?;
+        let parsed = claims_from_scope(lines[3], InvalidEntryPolicy::Reject)
+            .map_err(TokenError::InvalidClaim)?;
+
+        if now >= expires_at {
+            return Err(TokenError::Expired(expires_at));
+        }
+
+        Ok(Self {
+            issuer: String::from(lines[1]),
+            expires_at,
+            claims: parsed.claims,
+        })
+    }
+
+    /// Like `verify`, using the system clock.
+    pub fn verify_now(token: &str, key: &[u8]) -> Result<Self, TokenError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self::verify(token, key, now)
+    }
+}
+
+fn mac_for(key: &[u8]) -> HmacSha256 {
+    HmacSha256::new_from_slice(key).expect("HMAC can take a key of any size")
+}
+
+fn signature_for(data: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut mac = mac_for(key);
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn err_malformed(msg: &str) -> TokenError {
+    TokenError::Malformed(String::from(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims;
+
+    const KEY: &[u8] = b"secret-key";
+
+    fn make(list: &[&str]) -> CapabilityToken {
+        CapabilityToken::new("auth.example.com", 1000, claims(list)).unwrap()
+    }
+
+    fn forge(payload: &str) -> String {
+        let encoded = URL_SAFE_NO_PAD.encode(payload.as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(signature_for(encoded.as_bytes(), KEY));
+        format!("{}.{}", encoded, signature)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let token = make(&["read:projects.42", "admin:*", "read:projects.42"]);
+        let signed = token.sign(KEY);
+
+        let verified = CapabilityToken::verify(&signed, KEY, 999).unwrap();
+        assert_eq!(verified, token);
+        assert_eq!(verified.issuer(), "auth.example.com");
+        assert_eq!(verified.expires_at(), 1000);
+        assert_eq!(
+            verified.claims(),
+            &[Claim::new("admin", ""), Claim::new("read", "projects.42")]
+        );
+    }
+
+    #[test]
+    fn test_empty_claims() {
+        let token = make(&[]);
+        let verified = CapabilityToken::verify(&token.sign(KEY), KEY, 0).unwrap();
+        assert!(verified.claims().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_issuer() {
+        assert!(CapabilityToken::new("a\nb", 1000, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_expired() {
+        let signed = make(&["read:*"]).sign(KEY);
+        assert_eq!(
+            CapabilityToken::verify(&signed, KEY, 1000),
+            Err(TokenError::Expired(1000))
+        );
+    }
+
+    #[test]
+    fn test_bad_signature() {
+        let signed = make(&["read:*"]).sign(KEY);
+        assert_eq!(
+            CapabilityToken::verify(&signed, b"other-key", 0),
+            Err(TokenError::BadSignature)
+        );
+
+        let other = make(&["admin:*"]).sign(KEY);
+        let payload = other.split('.').next().unwrap();
+        let signature = signed.split('.').nth(1).unwrap();
+        let tampered = format!("{}.{}", payload, signature);
+        assert_eq!(
+            CapabilityToken::verify(&tampered, KEY, 0),
+            Err(TokenError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let list = ["", "abc", "a.b.c", "abc.!!!", "abc.", ".abc"];
+        for token in list.iter() {
+            match CapabilityToken::verify(token, KEY, 0) {
+                Err(TokenError::Malformed(_)) | Err(TokenError::BadSignature) => {}
+                other => panic!("token {:?} should be rejected, got {:?}", token, other),
+            }
+        }
+
+        let forged = forge("claims-v0\niss\n1000\nread:*");
+        assert!(matches!(
+            CapabilityToken::verify(&forged, KEY, 0),
+            Err(TokenError::Malformed(_))
+        ));
+
+        let forged = forge("claims-v1\niss\nsoon\nread:*");
+        assert!(matches!(
+            CapabilityToken::verify(&forged, KEY, 0),
+            Err(TokenError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_claim() {
+        let forged = forge("claims-v1\niss\n1000\nread:* bad-stuff.*");
+        assert_eq!(
+            CapabilityToken::verify(&forged, KEY, 0),
+            Err(TokenError::InvalidClaim(Error::Syntax(
+                "the given claim bad-stuff.* is not valid".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            format!("{}", TokenError::BadSignature),
+            "the token signature is not valid"
+        );
+        assert_eq!(
+            format!("{}", TokenError::Expired(10)),
+            "the token expired at 10"
+        );
+    }
+}
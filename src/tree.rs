@@ -0,0 +1,341 @@
+use crate::claim::Claim;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeStatus {
+    /// A claim in the list grants this node.
+    Granted,
+    /// A claim in the list grants an ancestor of this node.
+    Covered,
+    /// Only some descendants of this node are granted.
+    Partial,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct TreeNode {
+    granted: bool,
+    children: BTreeMap<String, TreeNode>,
+}
+
+/// The tree of verbs and subject segments of a list of claims, e.g.
+/// `read:projects.42` gives `read` → `projects` → `42`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ClaimTree {
+    verbs: BTreeMap<String, TreeNode>,
+}
+
+/// A node of a `ClaimTree`, as given by `ClaimTree::iter`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeEntry<'a> {
+    pub verb: &'a str,
+    /// Empty for the verb nodes.
+    pub subject: String,
+    /// 0 for the verb nodes.
+    pub depth: usize,
+    pub status: NodeStatus,
+    /// No sibling comes after this node.
+    pub is_last: bool,
+}
+
+impl<'a> TreeEntry<'a> {
+    pub fn name(&self) -> &str {
+        match self.subject.rfind('.') {
+            Some(idx) => &self.subject[idx + 1..],
+            None if self.subject.is_empty() => self.verb,
+            None => &self.subject,
+        }
+    }
+
+    pub fn to_claim(&self) -> Claim {
+        Claim::new(self.verb, &self.subject)
+    }
+}
+
+impl ClaimTree {
+    pub fn new<'a, I>(claims: I) -> Self
+    where
+        I: Iterator<Item = &'a Claim>,
+    {
+        let mut tree = Self::default();
+        for claim in claims {
+            let mut node = tree.verbs.entry(claim.verb.clone()).or_default();
+            if !claim.is_global() {
+                // claims built with `Claim::new` may have empty segments
+                for segment in claim.subject.split('.').filter(|s| !s.is_empty()) {
+                    node = node.children.entry(String::from(segment)).or_default();
+                }
+            }
+            node.granted = true;
+        }
+        tree
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verbs.is_empty()
+    }
+
+    /// Walks the tree depth-first, verbs and segments in alphabetical order.
+    pub fn iter(&self) -> TreeIter<'_> {
+        let stack: Vec<StackItem<'_>> = self
+            .verbs
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(idx, (verb, node))| StackItem {
+                verb,
+                subject: String::new(),
+                depth: 0,
+                covered: false,
+                is_last: idx == 0,
+                node,
+            })
+            .collect();
+        TreeIter { stack }
+    }
+
+    /// Renders the tree with one node per line, drawn with ASCII `|--` and
+    /// `` `-- `` branches, marking nodes granted by a claim with `[granted]`
+    /// and nodes under a granted one with `(covered)`.
+    pub fn to_ascii(&self) -> String {
+        let mut out = String::new();
+        let mut last_at_depth: Vec<bool> = Vec::new();
+
+        for entry in self.iter() {
+            last_at_depth.truncate(entry.depth);
+            last_at_depth.push(entry.is_last);
+
+            if entry.depth > 0 {
+                for last in last_at_depth[1..entry.depth].iter() {
+                    out.push_str(if *last { "    " } else { "|   " });
+                }
+                out.push_str(if entry.is_last { "`-- " } else { "|-- " });
+            }
+            out.push_str(entry.name());
+            match entry.status {
+                NodeStatus::Granted => out.push_str(" [granted]"),
+                NodeStatus::Covered => out.push_str(" (covered)"),
+                NodeStatus::Partial => {}
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders the tree as a Graphviz DOT digraph, with node ids being the
+    /// claim strings. Granted nodes are bold and covered nodes dashed. Quotes
+    /// and backslashes in ids and labels are escaped.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph claims {\n");
+        for entry in self.iter() {
+            let id = dot_escape(&entry.to_claim().to_string());
+            let style = match entry.status {
+                NodeStatus::Granted => ", style=bold",
+                NodeStatus::Covered => ", style=dashed",
+                NodeStatus::Partial => "",
+            };
+            let _ = writeln!(
+                out,
+                "  \"{}\" [label=\"{}\"{}];",
+                id,
+                dot_escape(entry.name()),
+                style
+            );
+
+            if let Some(parent) = entry.to_claim().parent() {
+                let parent = dot_escape(&parent.to_string());
+                let _ = writeln!(out, "  \"{}\" -> \"{}\";", parent, id);
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct StackItem<'a> {
+    verb: &'a str,
+    subject: String,
+    depth: usize,
+    covered: bool,
+    is_last: bool,
+    node: &'a TreeNode,
+}
+
+pub struct TreeIter<'a> {
+    stack: Vec<StackItem<'a>>,
+}
+
+impl<'a> Iterator for TreeIter<'a> {
+    type Item = TreeEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.stack.pop()?;
+        let status = if item.node.granted {
+            NodeStatus::Granted
+        } else if item.covered {
+            NodeStatus::Covered
+        } else {
+            NodeStatus::Partial
+        };
+
+        for (idx, (segment, child)) in item.node.children.iter().rev().enumerate() {
+            let subject = if item.subject.is_empty() {
+                segment.clone()
+            } else {
+                format!("{}.{}", item.subject, segment)
+            };
+            self.stack.push(StackItem {
+                verb: item.verb,
+                subject,
+                depth: item.depth + 1,
+                covered: item.covered || item.node.granted,
+                is_last: idx == 0,
+                node: child,
+            });
+        }
+
+        Some(TreeEntry {
+            verb: item.verb,
+            subject: item.subject,
+            depth: item.depth,
+            status,
+            is_last: item.is_last,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::claims;
+
+    fn tree(list: &[&str]) -> ClaimTree {
+        ClaimTree::new(claims(list).iter())
+    }
+
+    #[test]
+    fn test_empty() {
+        let t = tree(&[]);
+        assert!(t.is_empty());
+        assert_eq!(t.iter().count(), 0);
+        assert_eq!(t.to_ascii(), "");
+        assert_eq!(t.to_dot(), "digraph claims {\n}\n");
+    }
+
+    #[test]
+    fn test_iter() {
+        let t = tree(&[
+            "read:projects.42.x",
+            "read:projects",
+            "read:users.1",
+            "admin:*",
+        ]);
+        let entries: Vec<(String, usize, NodeStatus)> = t
+            .iter()
+            .map(|e| (e.to_claim().to_string(), e.depth, e.status))
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                ("admin:*".to_string(), 0, NodeStatus::Granted),
+                ("read:*".to_string(), 0, NodeStatus::Partial),
+                ("read:projects".to_string(), 1, NodeStatus::Granted),
+                ("read:projects.42".to_string(), 2, NodeStatus::Covered),
+                ("read:projects.42.x".to_string(), 3, NodeStatus::Granted),
+                ("read:users".to_string(), 1, NodeStatus::Partial),
+                ("read:users.1".to_string(), 2, NodeStatus::Granted),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_last_siblings() {
+        let t = tree(&["admin:*", "read:a.b", "read:a.c", "read:d"]);
+        let last: Vec<(String, bool)> = t
+            .iter()
+            .map(|e| (e.to_claim().to_string(), e.is_last))
+            .collect();
+        assert_eq!(
+            last,
+            vec![
+                ("admin:*".to_string(), false),
+                ("read:*".to_string(), true),
+                ("read:a".to_string(), false),
+                ("read:a.b".to_string(), false),
+                ("read:a.c".to_string(), true),
+                ("read:d".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entry_name() {
+        let t = tree(&["read:a.b"]);
+        let names: Vec<String> = t.iter().map(|e| e.name().to_string()).collect();
+        assert_eq!(names, vec!["read", "a", "b"]);
+    }
+
+    #[test]
+    fn test_ascii() {
+        let t = tree(&[
+            "read:projects.42.settings",
+            "read:projects",
+            "read:projects.7",
+            "read:users.1",
+            "admin:*",
+        ]);
+        let expected = "\
+admin [granted]
+read
+|-- projects [granted]
+|   |-- 42 (covered)
+|   |   `-- settings [granted]
+|   `-- 7 [granted]
+`-- users
+    `-- 1 [granted]
+";
+        assert_eq!(t.to_ascii(), expected);
+    }
+
+    #[test]
+    fn test_dot() {
+        let t = tree(&["read:a.b", "read:*"]);
+        let expected = "\
+digraph claims {
+  \"read:*\" [label=\"read\", style=bold];
+  \"read:a\" [label=\"a\", style=dashed];
+  \"read:*\" -> \"read:a\";
+  \"read:a.b\" [label=\"b\", style=bold];
+  \"read:a\" -> \"read:a.b\";
+}
+";
+        assert_eq!(t.to_dot(), expected);
+    }
+
+    #[test]
+    fn test_dot_escapes() {
+        let t = ClaimTree::new([Claim::new("read", "a\"b\\c")].iter());
+        let expected = "\
+digraph claims {
+  \"read:*\" [label=\"read\"];
+  \"read:a\\\"b\\\\c\" [label=\"a\\\"b\\\\c\", style=bold];
+  \"read:*\" -> \"read:a\\\"b\\\\c\";
+}
+";
+        assert_eq!(t.to_dot(), expected);
+    }
+
+    #[test]
+    fn test_skips_empty_segments() {
+        let list = [Claim::new("read", "a..b"), Claim::new("read", "a.b.")];
+        let t = ClaimTree::new(list.iter());
+        let subjects: Vec<String> = t.iter().map(|e| e.subject).collect();
+        assert_eq!(subjects, vec!["", "a", "a.b"]);
+        assert_eq!(t, tree(&["read:a.b"]));
+    }
+}